@@ -11,6 +11,11 @@ pub enum GoTrueError {
   #[error("request timeout:{0}")]
   RequestTimeout(String),
 
+  /// The gotrue server is reachable but can not serve the request, for example when the reverse
+  /// proxy in front of it responds with 502, 503 or 504.
+  #[error("service unavailable:{0}")]
+  ServiceUnavailable(String),
+
   #[error("invalid request:{0}")]
   InvalidRequest(String),
 
@@ -34,7 +39,7 @@ impl GoTrueError {
   pub fn is_network_error(&self) -> bool {
     matches!(
       self,
-      GoTrueError::Connect(_) | GoTrueError::RequestTimeout(_)
+      GoTrueError::Connect(_) | GoTrueError::RequestTimeout(_) | GoTrueError::ServiceUnavailable(_)
     )
   }
 }
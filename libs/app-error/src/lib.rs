@@ -103,6 +103,11 @@ pub enum AppError {
   #[error("{0}")]
   RequestTimeout(String),
 
+  /// A service that the request depends on, such as gotrue, is unreachable. The request can be
+  /// retried later.
+  #[error("Service unavailable:{0}")]
+  ServiceUnavailable(String),
+
   #[cfg(feature = "tokio_error")]
   #[error(transparent)]
   TokioJoinError(#[from] tokio::task::JoinError),
@@ -122,6 +127,27 @@ pub enum AppError {
 
   #[error("{0}")]
   PublishNamespaceAlreadyTaken(String),
+
+  #[error("{0}")]
+  PublishNameAlreadyTaken(String),
+
+  #[error("{0}")]
+  UserNotFound(String),
+
+  #[error("{0}")]
+  InvalidCredentials(String),
+
+  #[error("{0}")]
+  EmailNotConfirmed(String),
+
+  #[error("{0}")]
+  EmailAlreadyConfirmed(String),
+
+  #[error("{0}")]
+  PublishDisabled(String),
+
+  #[error("{0}")]
+  PublishVersionConflict(String),
 }
 
 impl AppError {
@@ -134,7 +160,10 @@ impl AppError {
   }
 
   pub fn is_network_error(&self) -> bool {
-    matches!(self, AppError::Connect(_) | AppError::RequestTimeout(_))
+    matches!(
+      self,
+      AppError::Connect(_) | AppError::RequestTimeout(_) | AppError::ServiceUnavailable(_)
+    )
   }
 
   pub fn is_unauthorized(&self) -> bool {
@@ -173,6 +202,7 @@ impl AppError {
       AppError::SerdeError(_) => ErrorCode::SerdeError,
       AppError::Connect(_) => ErrorCode::NetworkError,
       AppError::RequestTimeout(_) => ErrorCode::NetworkError,
+      AppError::ServiceUnavailable(_) => ErrorCode::ServiceUnavailable,
       #[cfg(feature = "tokio_error")]
       AppError::TokioJoinError(_) => ErrorCode::Internal,
       #[cfg(feature = "bincode_error")]
@@ -182,6 +212,13 @@ impl AppError {
       AppError::Utf8Error(_) => ErrorCode::Internal,
       AppError::PublishNamespaceNotSet(_) => ErrorCode::PublishNamespaceNotSet,
       AppError::PublishNamespaceAlreadyTaken(_) => ErrorCode::PublishNamespaceAlreadyTaken,
+      AppError::PublishNameAlreadyTaken(_) => ErrorCode::PublishNameAlreadyTaken,
+      AppError::UserNotFound(_) => ErrorCode::UserNotFound,
+      AppError::InvalidCredentials(_) => ErrorCode::InvalidCredentials,
+      AppError::EmailNotConfirmed(_) => ErrorCode::EmailNotConfirmed,
+      AppError::EmailAlreadyConfirmed(_) => ErrorCode::EmailAlreadyConfirmed,
+      AppError::PublishDisabled(_) => ErrorCode::PublishDisabled,
+      AppError::PublishVersionConflict(_) => ErrorCode::PublishVersionConflict,
     }
   }
 }
@@ -225,16 +262,23 @@ impl From<sqlx::Error> for AppError {
 impl From<crate::gotrue::GoTrueError> for AppError {
   fn from(err: crate::gotrue::GoTrueError) -> Self {
     match err {
-      GoTrueError::Connect(msg) => AppError::Connect(msg),
+      // The gotrue client only connects to the gotrue server, so a connect error means the
+      // server is unreachable rather than the credentials being wrong.
+      GoTrueError::Connect(msg) => AppError::ServiceUnavailable(msg),
+      GoTrueError::ServiceUnavailable(msg) => AppError::ServiceUnavailable(msg),
       GoTrueError::RequestTimeout(msg) => AppError::RequestTimeout(msg),
       GoTrueError::InvalidRequest(msg) => AppError::InvalidRequest(msg),
-      GoTrueError::ClientError(err) => AppError::OAuthError(err.to_string()),
+      GoTrueError::ClientError(err) => AppError::from(err),
       GoTrueError::Auth(err) => AppError::UserUnAuthorized(err),
       GoTrueError::Internal(err) => match (err.code, err.msg.as_str()) {
         (400, m) if m.starts_with("oauth error") => AppError::OAuthError(err.msg),
         (400, m) if m.starts_with("User already registered") => {
           AppError::UserAlreadyRegistered(err.msg)
         },
+        (_, m) if m.to_lowercase().contains("already confirmed") => {
+          AppError::EmailAlreadyConfirmed(err.msg)
+        },
+        (_, m) if m.to_lowercase().contains("user not found") => AppError::UserNotFound(err.msg),
         (401, _) => AppError::UserUnAuthorized(format!("{}:{}", err.code, err.msg)),
         (422, _) => AppError::InvalidRequest(err.msg),
         _ => AppError::OAuthError(err.msg),
@@ -245,6 +289,29 @@ impl From<crate::gotrue::GoTrueError> for AppError {
   }
 }
 
+/// Sign in failures are reported by gotrue as `invalid_grant`, the description tells them apart:
+/// https://github.com/supabase/gotrue/blob/cc07b4aa2ace75d9c8e46ae5107dbabadf944e87/internal/api/token.go#L140
+#[cfg(feature = "gotrue_error")]
+impl From<crate::gotrue::GotrueClientError> for AppError {
+  fn from(err: crate::gotrue::GotrueClientError) -> Self {
+    let description = err
+      .error_description
+      .as_deref()
+      .unwrap_or_default()
+      .to_lowercase();
+    match err.error.as_str() {
+      "invalid_grant" if description.contains("email not confirmed") => {
+        AppError::EmailNotConfirmed(err.to_string())
+      },
+      "invalid_grant" if description.contains("invalid login credentials") => {
+        AppError::InvalidCredentials(err.to_string())
+      },
+      _ if description.contains("user not found") => AppError::UserNotFound(err.to_string()),
+      _ => AppError::OAuthError(err.to_string()),
+    }
+  }
+}
+
 #[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
 #[derive(
   Eq,
@@ -292,6 +359,14 @@ pub enum ErrorCode {
   OverrideWithIncorrectData = 1029,
   PublishNamespaceNotSet = 1030,
   PublishNamespaceAlreadyTaken = 1031,
+  ServiceUnavailable = 1032,
+  PublishNameAlreadyTaken = 1033,
+  UserNotFound = 1034,
+  InvalidCredentials = 1035,
+  EmailNotConfirmed = 1036,
+  EmailAlreadyConfirmed = 1037,
+  PublishDisabled = 1038,
+  PublishVersionConflict = 1039,
 }
 
 impl ErrorCode {
@@ -5,7 +5,9 @@ use assert_json_diff::{
 };
 use bytes::Bytes;
 #[cfg(feature = "collab-sync")]
-use client_api::collab_sync::{SinkConfig, SyncObject, SyncPlugin};
+use client_api::collab_sync::{
+  SinkConfig, StreamConfig, SubscribeStream, SyncObject, SyncPlugin, UpdateEncoderVersion,
+};
 use client_api::entity::QueryWorkspaceMember;
 use client_api::ws::{WSClient, WSClientConfig};
 use collab::core::collab::{DataSource, MutexCollab};
@@ -570,6 +572,10 @@ impl TestClient {
         .subscribe_collab(object_id.to_string())
         .unwrap();
       let (sink, stream) = (handler.sink(), handler.stream());
+      let subscribe_stream: SubscribeStream<_> = {
+        let handler = handler.clone();
+        Box::new(move || handler.stream())
+      };
       let ws_connect_state = self.ws_client.subscribe_connect_state();
       let object = SyncObject::new(object_id, workspace_id, collab_type, &self.device_id);
       let sync_plugin = SyncPlugin::new(
@@ -577,10 +583,13 @@ impl TestClient {
         object,
         Arc::downgrade(&collab),
         sink,
-        SinkConfig::default(),
+        SinkConfig::default().encoder_version(UpdateEncoderVersion::V2),
         stream,
+        Some(subscribe_stream),
+        StreamConfig::default(),
         Some(handler),
         ws_connect_state,
+        None,
       );
       collab.lock().add_plugin(Box::new(sync_plugin));
     }
@@ -640,6 +649,10 @@ impl TestClient {
         .subscribe_collab(object_id.to_string())
         .unwrap();
       let (sink, stream) = (handler.sink(), handler.stream());
+      let subscribe_stream: SubscribeStream<_> = {
+        let handler = handler.clone();
+        Box::new(move || handler.stream())
+      };
       let ws_connect_state = self.ws_client.subscribe_connect_state();
       let object = SyncObject::new(object_id, workspace_id, collab_type, &self.device_id);
       let sync_plugin = SyncPlugin::new(
@@ -647,10 +660,13 @@ impl TestClient {
         object,
         Arc::downgrade(&collab),
         sink,
-        SinkConfig::default(),
+        SinkConfig::default().encoder_version(UpdateEncoderVersion::V2),
         stream,
+        Some(subscribe_stream),
+        StreamConfig::default(),
         Some(handler),
         ws_connect_state,
+        None,
       );
 
       collab.lock().add_plugin(Box::new(sync_plugin));
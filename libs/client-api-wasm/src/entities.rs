@@ -1,9 +1,10 @@
 use client_api::entity::AFUserProfile;
 use client_api::error::{AppResponseError, ErrorCode};
-use collab_entity::{CollabType, EncodedCollab};
+use collab::entity::{EncodedCollab, EncoderVersion};
+use collab_entity::CollabType;
 use database_entity::dto::{
-  AFUserWorkspaceInfo, AFWorkspace, BatchQueryCollabResult, QueryCollab,
-  QueryCollabParams, QueryCollabResult,
+  AFUserWorkspaceInfo, AFWorkspace, BatchQueryCollabResult, QueryCollab, QueryCollabParams,
+  QueryCollabResult,
 };
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -76,13 +77,14 @@ pub struct User {
 from_struct_for_jsvalue!(User);
 impl From<AFUserProfile> for User {
   fn from(profile: AFUserProfile) -> Self {
+    let icon_url = profile.icon_url();
     User {
       uid: profile.uid.to_string(),
       uuid: profile.uuid.to_string(),
       email: profile.email,
       name: profile.name,
       latest_workspace_id: profile.latest_workspace_id.to_string(),
-      icon_url: None,
+      icon_url,
     }
   }
 }
@@ -177,12 +179,21 @@ pub enum ClientEncoderVersion {
 
 from_struct_for_jsvalue!(ClientEncodeCollab);
 
+impl From<EncoderVersion> for ClientEncoderVersion {
+  fn from(version: EncoderVersion) -> Self {
+    match version {
+      EncoderVersion::V1 => ClientEncoderVersion::V1,
+      EncoderVersion::V2 => ClientEncoderVersion::V2,
+    }
+  }
+}
+
 impl From<EncodedCollab> for ClientEncodeCollab {
   fn from(collab: EncodedCollab) -> Self {
     ClientEncodeCollab {
       state_vector: collab.state_vector.to_vec(),
       doc_state: collab.doc_state.to_vec(),
-      version: ClientEncoderVersion::V1,
+      version: ClientEncoderVersion::from(collab.version),
     }
   }
 }
@@ -211,30 +222,62 @@ impl From<ClientQueryCollab> for QueryCollab {
 
 #[derive(Tsify, Serialize, Deserialize, Default)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
-pub struct BatchClientEncodeCollab(pub HashMap<String, ClientEncodeCollab>);
+pub struct BatchClientEncodeCollab {
+  pub collabs: HashMap<String, ClientEncodeCollab>,
+  /// The objects that couldn't be returned, keyed by object id. The code is
+  /// [ErrorCode::RecordNotFound] when the object doesn't exist, and [ErrorCode::Internal] when
+  /// the object couldn't be decoded.
+  pub errors: HashMap<String, ClientResponse>,
+}
 
 from_struct_for_jsvalue!(BatchClientEncodeCollab);
 
+/// The error of [QueryCollabResult::Failed] when the collab doesn't exist.
+const QUERY_COLLAB_NOT_FOUND: &str = "Record not found";
+
 impl From<BatchQueryCollabResult> for BatchClientEncodeCollab {
   fn from(result: BatchQueryCollabResult) -> Self {
-    let mut hash_map = HashMap::new();
+    let mut collabs = HashMap::new();
+    let mut errors = HashMap::new();
 
     result.0.into_iter().for_each(|(k, v)| match v {
+      // The encoder version is stored along with the encoded collab, so decode_from_bytes returns
+      // the doc state with the version it was encoded with.
       QueryCollabResult::Success { encode_collab_v1 } => {
-        EncodedCollab::decode_from_bytes(&encode_collab_v1)
-          .map(|collab| {
-            hash_map.insert(k, ClientEncodeCollab::from(collab));
-          })
-          .unwrap_or_else(|err| {
-            tracing::error!("Failed to decode collab: {:?}", err);
-          });
+        match EncodedCollab::decode_from_bytes(&encode_collab_v1) {
+          Ok(collab) => {
+            collabs.insert(k, ClientEncodeCollab::from(collab));
+          },
+          Err(err) => {
+            tracing::error!("Failed to decode collab {}: {:?}", k, err);
+            errors.insert(
+              k,
+              ClientResponse {
+                code: ErrorCode::Internal,
+                message: format!("Failed to decode collab: {}", err),
+              },
+            );
+          },
+        }
       },
-      QueryCollabResult::Failed { .. } => {
-        tracing::error!("Failed to get collab: {:?}", k);
+      QueryCollabResult::Failed { error } => {
+        tracing::error!("Failed to get collab {}: {}", k, error);
+        let code = if error == QUERY_COLLAB_NOT_FOUND {
+          ErrorCode::RecordNotFound
+        } else {
+          ErrorCode::InvalidRequest
+        };
+        errors.insert(
+          k,
+          ClientResponse {
+            code,
+            message: error,
+          },
+        );
       },
     });
 
-    BatchClientEncodeCollab(hash_map)
+    BatchClientEncodeCollab { collabs, errors }
   }
 }
 
@@ -256,6 +299,7 @@ pub struct PublishViewPayload {
 pub struct PublishInfo {
   pub namespace: Option<String>,
   pub publish_name: String,
+  pub metadata_version: i32,
 }
 from_struct_for_jsvalue!(PublishViewMeta);
 from_struct_for_jsvalue!(PublishViewPayload);
@@ -212,6 +212,7 @@ impl ClientAPI {
       Ok(info) => Ok(PublishInfo {
         namespace: info.namespace,
         publish_name: info.publish_name,
+        metadata_version: info.metadata_version,
       }),
       Err(err) => Err(ClientResponse::from(err)),
     }
@@ -5,6 +5,7 @@ use crate::collab_sync::{SinkConfig, SyncError, SyncObject};
 use anyhow::Error;
 use collab::core::origin::{CollabClient, CollabOrigin};
 use collab_rt_entity::{ClientCollabMessage, MsgId, ServerCollabMessage, SinkMessage};
+use collab_rt_protocol::UpdateEncoderVersion;
 use futures_util::SinkExt;
 use std::collections::BinaryHeap;
 use std::collections::{HashMap, HashSet};
@@ -393,6 +394,19 @@ where
     }
   }
 
+  /// Returns true if all the queued messages, except the pings, were acknowledged by the remote.
+  pub(crate) fn encoder_version(&self) -> UpdateEncoderVersion {
+    self.config.encoder_version
+  }
+
+  pub(crate) fn is_all_acked(&self) -> bool {
+    !self
+      .message_queue
+      .lock()
+      .iter()
+      .any(|item| !item.message().is_ping_sync())
+  }
+
   /// Notify the sink to process the next message.
   pub(crate) fn notify_next(&self) {
     let _ = self.notifier.send(SinkSignal::Proceed);
@@ -1,26 +1,40 @@
 use crate::af_spawn;
 use crate::collab_sync::{
-  start_sync, CollabSink, MissUpdateReason, SyncError, SyncObject, SyncReason,
+  start_sync, CollabSink, MissUpdateReason, RemoteSyncState, RemoteSyncStatus, StreamConfig,
+  SyncError, SyncMetrics, SyncObject, SyncProgress, SyncReason,
 };
 
 use client_api_entity::{validate_data_for_folder, CollabType};
-use collab::core::collab::MutexCollab;
+use collab::core::collab::{DataSource, MutexCollab};
 use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
 use collab_rt_entity::{AckCode, ClientCollabMessage, ServerCollabMessage, ServerInit, UpdateSync};
 use collab_rt_protocol::{
-  handle_message_follow_protocol, ClientSyncProtocol, Message, MessageReader, SyncMessage,
+  handle_message_follow_protocol_with_version, ClientSyncProtocol, CustomMessage, Message,
+  MessageReader, SyncMessage, UpdateEncoderVersion,
 };
 use futures_util::{SinkExt, StreamExt};
 use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use tokio::select;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, watch, Mutex};
 use tokio_util::sync::CancellationToken;
 
 use tracing::{error, instrument, trace, warn};
 use yrs::encoding::read::Cursor;
-use yrs::updates::decoder::DecoderV1;
+use yrs::updates::decoder::{Decode, DecoderV1};
+use yrs::updates::encoder::Encode;
+use yrs::{merge_updates_v1, ReadTxn, StateVector, Update};
+
+/// The interval between the attempts to start the init sync asked by the server while the collab
+/// is locked.
+const FORCE_RESYNC_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Creates a new subscription to the messages of a collab. It is used to receive the messages
+/// again after the current stream yields an error.
+pub type SubscribeStream<Stream> = Box<dyn Fn() -> Stream + Send + Sync>;
 
 /// Use to continuously receive updates from remote.
 pub struct ObserveCollab<Sink, Stream> {
@@ -30,8 +44,9 @@ pub struct ObserveCollab<Sink, Stream> {
   phantom_sink: PhantomData<Sink>,
   phantom_stream: PhantomData<Stream>,
   // Use sequence number to check if the received updates/broadcasts are continuous.
-  #[allow(dead_code)]
-  seq_num_counter: Arc<SeqNumCounter>,
+  pub(crate) seq_num_counter: Arc<SeqNumCounter>,
+  remote_sync_state_rx: watch::Receiver<RemoteSyncState>,
+  metrics: Arc<SyncMetrics>,
 }
 
 impl<Sink, Stream> Drop for ObserveCollab<Sink, Stream> {
@@ -47,27 +62,45 @@ where
   Sink: SinkExt<Vec<ClientCollabMessage>, Error = E> + Send + Sync + Unpin + 'static,
   Stream: StreamExt<Item = Result<ServerCollabMessage, E>> + Send + Sync + Unpin + 'static,
 {
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     origin: CollabOrigin,
     object: SyncObject,
     stream: Stream,
+    subscribe_stream: Option<SubscribeStream<Stream>>,
+    stream_config: StreamConfig,
     weak_collab: Weak<MutexCollab>,
     sink: Weak<CollabSink<Sink>>,
+    force_resync_tx: broadcast::Sender<String>,
+    collab_deleted_tx: broadcast::Sender<String>,
+    sync_progress_tx: broadcast::Sender<SyncProgress>,
   ) -> Self {
     let object_id = object.object_id.clone();
     let cloned_weak_collab = weak_collab.clone();
     let seq_num_counter = Arc::new(SeqNumCounter::default());
     let cloned_seq_num_counter = seq_num_counter.clone();
     let init_sync_cancel_token = Arc::new(Mutex::new(CancellationToken::new()));
+    let (remote_sync_state_tx, remote_sync_state_rx) = watch::channel(RemoteSyncState {
+      object_id: object_id.clone(),
+      status: RemoteSyncStatus::InitSync,
+    });
     let arc_object = Arc::new(object);
+    let metrics = Arc::new(SyncMetrics::default());
     af_spawn(ObserveCollab::<Sink, Stream>::observer_collab_message(
       origin,
       arc_object,
       stream,
+      subscribe_stream,
+      stream_config,
       cloned_weak_collab,
       sink,
       cloned_seq_num_counter,
       init_sync_cancel_token,
+      force_resync_tx,
+      collab_deleted_tx,
+      sync_progress_tx,
+      remote_sync_state_tx,
+      metrics.clone(),
     ));
     Self {
       object_id,
@@ -75,19 +108,50 @@ where
       phantom_sink: Default::default(),
       phantom_stream: Default::default(),
       seq_num_counter,
+      remote_sync_state_rx,
+      metrics,
     }
   }
 
+  pub fn metrics(&self) -> Arc<SyncMetrics> {
+    self.metrics.clone()
+  }
+
+  pub fn subscribe_remote_sync_state(&self) -> watch::Receiver<RemoteSyncState> {
+    self.remote_sync_state_rx.clone()
+  }
+
+  pub fn seq_status(&self) -> SeqNumSnapshot {
+    self.seq_num_counter.snapshot()
+  }
+
   // Spawn the stream that continuously reads the doc's updates from remote.
+  #[allow(clippy::too_many_arguments)]
   async fn observer_collab_message(
     origin: CollabOrigin,
     object: Arc<SyncObject>,
     mut stream: Stream,
+    subscribe_stream: Option<SubscribeStream<Stream>>,
+    stream_config: StreamConfig,
     weak_collab: Weak<MutexCollab>,
     weak_sink: Weak<CollabSink<Sink>>,
     seq_num_counter: Arc<SeqNumCounter>,
     cancel_token: Arc<Mutex<CancellationToken>>,
+    force_resync_tx: broadcast::Sender<String>,
+    collab_deleted_tx: broadcast::Sender<String>,
+    sync_progress_tx: broadcast::Sender<SyncProgress>,
+    remote_sync_state_tx: watch::Sender<RemoteSyncState>,
+    metrics: Arc<SyncMetrics>,
   ) {
+    let set_status = |status: RemoteSyncStatus| {
+      remote_sync_state_tx.send_if_modified(|state| {
+        let modified = state.status != status;
+        state.status = status;
+        modified
+      });
+    };
+    let folder_snapshot = Arc::new(FolderSnapshot::default());
+    let mut reconnect_attempts = 0;
     while let Some(collab_message_result) = stream.next().await {
       let collab = match weak_collab.upgrade() {
         Some(collab) => collab,
@@ -100,31 +164,72 @@ where
       };
 
       let msg = match collab_message_result {
-        Ok(msg) => msg,
+        Ok(msg) => {
+          reconnect_attempts = 0;
+          msg
+        },
         Err(err) => {
+          // The error might be caused by a transient network issue, so subscribe again after a
+          // backoff. Without a way to subscribe, keep receiving from the current stream. The
+          // broadcasts that are missed in the meantime are detected by the sequence number check
+          // and pulled from the remote.
+          reconnect_attempts += 1;
+          set_status(RemoteSyncStatus::Errored);
+          if reconnect_attempts > stream_config.max_reconnect_attempts {
+            warn!(
+              "{} stream error:{}, stop receive incoming changes",
+              object.object_id,
+              err.into()
+            );
+            break;
+          }
+
+          let delay = stream_config.reconnect_delay_for_attempt(reconnect_attempts);
           warn!(
-            "{} stream error:{}, stop receive incoming changes",
+            "{} stream error:{}, retry receive incoming changes in {:?}",
             object.object_id,
-            err.into()
+            err.into(),
+            delay
           );
-          break;
+          drop(collab);
+          drop(sink);
+          tokio::time::sleep(delay).await;
+          if let Some(subscribe_stream) = &subscribe_stream {
+            stream = subscribe_stream();
+          }
+          continue;
         },
       };
 
-      if let Err(error) = ObserveCollab::<Sink, Stream>::process_remote_message(
+      // Broadcasts don't have a msg_id. The other messages are the replies to the requests of
+      // the collab, such as the init sync.
+      let is_reply = msg.msg_id().is_some();
+      let result = ObserveCollab::<Sink, Stream>::process_remote_message(
         &object,
         &collab,
         &sink,
         msg,
         &seq_num_counter,
+        &sync_progress_tx,
+        &folder_snapshot,
+        stream_config.catch_apply_panic,
       )
-      .await
-      {
+      .await;
+      if result.is_ok() {
+        let is_errored = remote_sync_state_tx.borrow().status == RemoteSyncStatus::Errored;
+        if is_reply || is_errored {
+          set_status(RemoteSyncStatus::Synced);
+        }
+      }
+
+      if let Err(error) = result {
         match error {
           SyncError::MissUpdates {
             state_vector_v1,
             reason,
           } => {
+            metrics.incr_miss_updates();
+            set_status(RemoteSyncStatus::PullingMissingUpdates);
             let mut cancel_token_lock = cancel_token.lock().await;
             cancel_token_lock.cancel();
             let new_cancel_token = CancellationToken::new();
@@ -135,6 +240,7 @@ where
             let cloned_object = object.clone();
             let collab = collab.clone();
             let sink = sink.clone();
+            let delay = stream_config.pull_missing_updates_delay;
             tokio::spawn(async move {
               select! {
                 _ = new_cancel_token.cancelled() => {
@@ -142,7 +248,7 @@ where
                       trace!("{} receive cancel signal, cancel pull missing updates", cloned_object.object_id);
                     }
                 },
-                _ = tokio::time::sleep(tokio::time::Duration::from_secs(3)) => {
+                _ = tokio::time::sleep(delay) => {
                    Self::pull_missing_updates(&cloned_origin, &cloned_object, &collab, &sink, state_vector_v1, reason)
                    .await;
                 }
@@ -150,6 +256,8 @@ where
             });
           },
           SyncError::CannotApplyUpdate => {
+            metrics.incr_cannot_apply_update();
+            set_status(RemoteSyncStatus::InitSync);
             if let Some(lock_guard) = collab.try_lock() {
               if let Err(err) = start_sync(
                 origin.clone(),
@@ -162,12 +270,34 @@ where
               }
             }
           },
+          SyncError::ForceResync => {
+            // The server asks the client to discard its sync state. Reset the sequence numbers so
+            // that the broadcasts after the init sync are not treated as missing updates.
+            seq_num_counter.reset();
+            set_status(RemoteSyncStatus::InitSync);
+            let _ = force_resync_tx.send(object.object_id.clone());
+            Self::force_resync(&origin, &object, &collab, &sink).await;
+          },
+          SyncError::CollabDeleted => {
+            // The collab was deleted on the server. Stop syncing it and let the embedder know,
+            // so it can close the collab.
+            trace!(
+              "{} was deleted, stop receive incoming changes",
+              object.object_id
+            );
+            sink.pause();
+            let _ = collab_deleted_tx.send(object.object_id.clone());
+            break;
+          },
           SyncError::OverrideWithIncorrectData(_) => {
+            metrics.incr_override_with_incorrect_data();
             error!("Error while processing message: {}", error);
+            set_status(RemoteSyncStatus::Errored);
             break;
           },
           _ => {
             error!("Error while processing message: {}", error);
+            set_status(RemoteSyncStatus::Errored);
           },
         }
       }
@@ -175,12 +305,16 @@ where
   }
 
   /// Continuously handle messages from the remote doc
+  #[allow(clippy::too_many_arguments)]
   async fn process_remote_message(
     object: &SyncObject,
     collab: &Arc<MutexCollab>,
     sink: &Arc<CollabSink<Sink>>,
     msg: ServerCollabMessage,
     seq_num_counter: &Arc<SeqNumCounter>,
+    sync_progress_tx: &broadcast::Sender<SyncProgress>,
+    folder_snapshot: &Arc<FolderSnapshot>,
+    catch_apply_panic: bool,
   ) -> Result<(), SyncError> {
     if cfg!(feature = "sync_verbose_log") {
       trace!("handle server: {}", msg);
@@ -193,6 +327,16 @@ where
         return Err(SyncError::CannotApplyUpdate);
       }
 
+      // the collab was deleted on the server, the client should stop syncing it.
+      if ack_code == AckCode::CollabDeleted {
+        return Err(SyncError::CollabDeleted);
+      }
+
+      // the server asks all the clients to perform a fresh init sync.
+      if ack_code == AckCode::ForceResync {
+        return Err(SyncError::ForceResync);
+      }
+
       if ack_code == AckCode::MissUpdate {
         return Err(SyncError::MissUpdates {
           state_vector_v1: Some(ack.payload.to_vec()),
@@ -205,7 +349,16 @@ where
     match msg.msg_id() {
       None => {
         // apply the broadcast data and then check the continuity of the broadcast sequence number.
-        Self::process_message_follow_protocol(object, &msg, collab, sink).await?;
+        Self::process_message_follow_protocol(
+          object,
+          &msg,
+          collab,
+          sink,
+          sync_progress_tx,
+          folder_snapshot,
+          catch_apply_panic,
+        )
+        .await?;
         sink.notify_next();
 
         if let ServerCollabMessage::ServerBroadcast(ref data) = msg {
@@ -220,7 +373,16 @@ where
           .await?;
 
         if is_valid {
-          Self::process_message_follow_protocol(object, &msg, collab, sink).await?;
+          Self::process_message_follow_protocol(
+            object,
+            &msg,
+            collab,
+            sink,
+            sync_progress_tx,
+            folder_snapshot,
+            catch_apply_panic,
+          )
+          .await?;
         }
         sink.notify_next();
         Ok(())
@@ -248,78 +410,310 @@ where
     }
   }
 
+  /// Starts the init sync asked by the server. The sequence numbers were already reset, so the
+  /// sync must not be dropped: when the collab is locked, it is retried until the lock is free.
+  async fn force_resync(
+    origin: &CollabOrigin,
+    object: &SyncObject,
+    collab: &Arc<MutexCollab>,
+    sink: &Arc<CollabSink<Sink>>,
+  ) {
+    loop {
+      if let Some(lock_guard) = collab.try_lock() {
+        if let Err(err) = start_sync(
+          origin.clone(),
+          object,
+          &lock_guard,
+          sink,
+          SyncReason::ServerForceResync,
+        ) {
+          error!("Error while start sync: {}", err);
+        }
+        return;
+      }
+      tokio::time::sleep(FORCE_RESYNC_RETRY_INTERVAL).await;
+    }
+  }
+
   async fn process_message_follow_protocol(
     sync_object: &SyncObject,
     msg: &ServerCollabMessage,
     collab: &Arc<MutexCollab>,
     sink: &Arc<CollabSink<Sink>>,
+    sync_progress_tx: &broadcast::Sender<SyncProgress>,
+    folder_snapshot: &Arc<FolderSnapshot>,
+    catch_apply_panic: bool,
   ) -> Result<(), SyncError> {
     if msg.payload().is_empty() {
       return Ok(());
     }
 
+    if !catch_apply_panic {
+      return Self::apply_message_follow_protocol(
+        sync_object,
+        msg.payload(),
+        msg.origin(),
+        collab,
+        sink,
+        sync_progress_tx,
+        folder_snapshot,
+      );
+    }
+
     let payload = msg.payload().clone();
     let message_origin = msg.origin().clone();
     let sink = sink.clone();
     let sync_object = sync_object.clone();
     let collab = collab.clone();
+    let sync_progress_tx = sync_progress_tx.clone();
+    let folder_snapshot = folder_snapshot.clone();
 
     // workaround for panic when applying updates. It can be removed in the future
     let result = tokio::spawn(async move {
-      if let Some(mut collab) = collab.try_lock() {
-        let mut decoder = DecoderV1::new(Cursor::new(&payload));
-        let reader = MessageReader::new(&mut decoder);
-        for yrs_message in reader {
-          let msg = yrs_message?;
-
-          // When the client receives a SyncStep1 message, it indicates that the server is requesting
-          // the client to send updates that the server is missing. This typically occurs when the client
-          // has been editing offline, resulting in the client's version of the collaboration object
-          // being ahead of the server's version. In response, the client prepares to send the missing updates.
-          let is_server_sync_step_1 = matches!(msg, Message::Sync(SyncMessage::SyncStep1(_)));
-
-          // If the collaboration object is of type [CollabType::Folder], data validation is required
-          // before sending the SyncStep1 to the server.
-          if is_server_sync_step_1 && sync_object.collab_type == CollabType::Folder {
-            validate_data_for_folder(&collab, &sync_object.workspace_id)
-              .map_err(|err| SyncError::OverrideWithIncorrectData(err.to_string()))?;
+      Self::apply_message_follow_protocol(
+        &sync_object,
+        &payload,
+        &message_origin,
+        &collab,
+        &sink,
+        &sync_progress_tx,
+        &folder_snapshot,
+      )
+    })
+    .await;
+
+    result.unwrap_or_else(|err| {
+      error!("Panic while processing message: {:?}", err);
+      Err(SyncError::Internal(anyhow::anyhow!(
+        "Panic while processing message"
+      )))
+    })
+  }
+
+  fn apply_message_follow_protocol(
+    sync_object: &SyncObject,
+    payload: &[u8],
+    message_origin: &CollabOrigin,
+    collab: &Arc<MutexCollab>,
+    sink: &Arc<CollabSink<Sink>>,
+    sync_progress_tx: &broadcast::Sender<SyncProgress>,
+    folder_snapshot: &FolderSnapshot,
+  ) -> Result<(), SyncError> {
+    if let Some(mut collab) = collab.try_lock() {
+      let mut pending_payloads = vec![];
+      let mut decoder = DecoderV1::new(Cursor::new(payload));
+      let reader = MessageReader::new(&mut decoder);
+      let mut progress = SyncProgress {
+        object_id: sync_object.object_id.clone(),
+        applied_updates: 0,
+        applied_bytes: 0,
+        total_bytes: payload.len(),
+      };
+      // The updates are encoded with v1, unless a [CustomMessage::EncoderVersion] that precedes
+      // them in the payload says otherwise.
+      let mut version = UpdateEncoderVersion::V1;
+      for yrs_message in reader {
+        let msg = yrs_message?;
+        if let Message::Custom(CustomMessage::EncoderVersion(payload_version)) = msg {
+          trace!(
+            "{} encoder version: {:?}",
+            sync_object.object_id,
+            payload_version
+          );
+          version = payload_version;
+          continue;
+        }
+
+        // When the client receives a SyncStep1 message, it indicates that the server is requesting
+        // the client to send updates that the server is missing. This typically occurs when the client
+        // has been editing offline, resulting in the client's version of the collaboration object
+        // being ahead of the server's version. In response, the client prepares to send the missing updates.
+        let is_server_sync_step_1 = matches!(msg, Message::Sync(SyncMessage::SyncStep1(_)));
+        let sync_step_2_len = match &msg {
+          Message::Sync(SyncMessage::SyncStep2(update)) => Some(update.len()),
+          _ => None,
+        };
+
+        // If the collaboration object is of type [CollabType::Folder], data validation is required
+        // before sending the SyncStep1 to the server. The updates sent by the server are validated
+        // against a snapshot of the folder before they are applied, because an applied update can
+        // not be reverted.
+        if sync_object.collab_type == CollabType::Folder {
+          match &msg {
+            Message::Sync(SyncMessage::SyncStep1(_)) => {
+              validate_data_for_folder(&collab, &sync_object.workspace_id)
+                .map_err(|err| SyncError::OverrideWithIncorrectData(err.to_string()))?;
+            },
+            Message::Sync(SyncMessage::SyncStep2(update))
+            | Message::Sync(SyncMessage::Update(update)) => {
+              folder_snapshot.validate_update(&collab, sync_object, update, version)?;
+            },
+            _ => {},
           }
+        }
 
-          if let Some(return_payload) =
-            handle_message_follow_protocol(&message_origin, &ClientSyncProtocol, &mut collab, msg)?
-          {
+        if let Some(return_payload) = handle_message_follow_protocol_with_version(
+          message_origin,
+          &ClientSyncProtocol,
+          &mut collab,
+          msg,
+          version,
+        )? {
+          if is_server_sync_step_1 {
+            // keep the order of the messages, the updates that were generated before the SyncStep1
+            // are queued first.
+            Self::queue_update_payloads(
+              sync_object,
+              message_origin,
+              sink,
+              std::mem::take(&mut pending_payloads),
+            );
             let object_id = sync_object.object_id.clone();
             sink.queue_msg(|msg_id| {
-              if is_server_sync_step_1 {
-                ClientCollabMessage::new_server_init_sync(ServerInit::new(
-                  message_origin.clone(),
-                  object_id,
-                  return_payload,
-                  msg_id,
-                ))
-              } else {
-                ClientCollabMessage::new_update_sync(UpdateSync::new(
-                  message_origin.clone(),
-                  object_id,
-                  return_payload,
-                  msg_id,
-                ))
-              }
+              ClientCollabMessage::new_server_init_sync(ServerInit::new(
+                message_origin.clone(),
+                object_id,
+                return_payload,
+                msg_id,
+              ))
             });
+          } else {
+            pending_payloads.push(return_payload);
           }
         }
+
+        // Report the progress after each SyncStep2 is applied, so that the progress of a large
+        // init sync can be displayed.
+        if let Some(len) = sync_step_2_len {
+          progress.applied_updates += 1;
+          progress.applied_bytes += len;
+          let _ = sync_progress_tx.send(progress.clone());
+        }
       }
-      Ok::<_, SyncError>(())
-    })
-    .await;
+      Self::queue_update_payloads(sync_object, message_origin, sink, pending_payloads);
+    }
+    Ok(())
+  }
 
-    result.unwrap_or_else(|err| {
-      error!("Panic while processing message: {:?}", err);
-      Err(SyncError::Internal(anyhow::anyhow!(
-        "Panic while processing message"
-      )))
+  /// Queues the payloads produced by consecutive messages as a single [UpdateSync], instead of
+  /// flooding the sink with one message per payload.
+  fn queue_update_payloads(
+    sync_object: &SyncObject,
+    message_origin: &CollabOrigin,
+    sink: &Arc<CollabSink<Sink>>,
+    payloads: Vec<Vec<u8>>,
+  ) {
+    if payloads.is_empty() {
+      return;
+    }
+
+    let payload = merge_update_payloads(payloads);
+    let object_id = sync_object.object_id.clone();
+    sink.queue_msg(|msg_id| {
+      ClientCollabMessage::new_update_sync(UpdateSync::new(
+        message_origin.clone(),
+        object_id,
+        payload,
+        msg_id,
+      ))
+    });
+  }
+}
+
+/// Merges the payloads into a single payload. When every payload is a single update, the updates
+/// are merged into one [SyncMessage::Update]. Otherwise, the encoded messages are concatenated,
+/// which can still be read by the [MessageReader] one message after another.
+fn merge_update_payloads(mut payloads: Vec<Vec<u8>>) -> Vec<u8> {
+  if payloads.len() == 1 {
+    return payloads.remove(0);
+  }
+
+  let updates = payloads
+    .iter()
+    .map(|payload| {
+      let mut decoder = DecoderV1::new(Cursor::new(payload));
+      let mut reader = MessageReader::new(&mut decoder);
+      match (reader.next(), reader.next()) {
+        (Some(Ok(Message::Sync(SyncMessage::Update(update)))), None)
+        | (Some(Ok(Message::Sync(SyncMessage::SyncStep2(update)))), None) => Some(update),
+        _ => None,
+      }
     })
+    .collect::<Option<Vec<_>>>();
+
+  if let Some(updates) = updates {
+    match merge_updates_v1(&updates) {
+      Ok(update) => return Message::Sync(SyncMessage::Update(update)).encode_v1(),
+      Err(err) => warn!("Failed to merge updates: {}", err),
+    }
   }
+  payloads.concat()
+}
+
+/// A copy of the folder that the updates sent by the server are applied to, and validated
+/// against, before they are applied to the folder. The snapshot is brought up to date with the
+/// changes made to the folder since the previous validation, so the whole folder is only encoded
+/// when the snapshot is created, or again after an update fails the validation.
+#[derive(Default)]
+struct FolderSnapshot(parking_lot::Mutex<Option<Collab>>);
+
+impl FolderSnapshot {
+  /// Applies the `update` to the snapshot and checks that the result is still a valid folder.
+  /// Returns [SyncError::OverrideWithIncorrectData] if the update would corrupt the folder.
+  fn validate_update(
+    &self,
+    folder: &Collab,
+    sync_object: &SyncObject,
+    update: &[u8],
+    version: UpdateEncoderVersion,
+  ) -> Result<(), SyncError> {
+    let mut snapshot = self.0.lock();
+    // The snapshot is taken out, so that it is discarded if the update can't be applied or
+    // doesn't pass the validation.
+    let snapshot_folder = match snapshot.take() {
+      Some(snapshot_folder) => {
+        let diff = folder
+          .transact()
+          .encode_diff_v1(&snapshot_folder.transact().state_vector());
+        let diff = Update::decode_v1(&diff)?;
+        apply_update(&snapshot_folder, sync_object, diff)?;
+        snapshot_folder
+      },
+      None => {
+        let doc_state = folder
+          .transact()
+          .encode_state_as_update_v1(&StateVector::default());
+        Collab::new_with_source(
+          CollabOrigin::Empty,
+          &sync_object.object_id,
+          DataSource::DocStateV1(doc_state),
+          vec![],
+          false,
+        )
+        .map_err(|err| SyncError::Internal(anyhow::anyhow!("{}", err)))?
+      },
+    };
+
+    apply_update(
+      &snapshot_folder,
+      sync_object,
+      version.decode_update(update)?,
+    )?;
+    validate_data_for_folder(&snapshot_folder, &sync_object.workspace_id)
+      .map_err(|err| SyncError::OverrideWithIncorrectData(err.to_string()))?;
+    *snapshot = Some(snapshot_folder);
+    Ok(())
+  }
+}
+
+fn apply_update(
+  collab: &Collab,
+  sync_object: &SyncObject,
+  update: Update,
+) -> Result<(), SyncError> {
+  collab
+    .with_origin_transact_mut(|txn| txn.try_apply_update(update))
+    .map_err(|err| SyncError::YrsApplyUpdate(format!("{}: {}", sync_object.object_id, err)))
 }
 
 #[derive(Default)]
@@ -335,7 +729,31 @@ pub struct SeqNumCounter {
   pub miss_update_counter: AtomicU32,
 }
 
+/// A point-in-time copy of the [SeqNumCounter]. After a successful sync, `ack_seq` is equal to
+/// `broadcast_seq`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SeqNumSnapshot {
+  pub broadcast_seq: u32,
+  pub ack_seq: u32,
+  pub miss_update_count: u32,
+}
+
 impl SeqNumCounter {
+  pub fn snapshot(&self) -> SeqNumSnapshot {
+    SeqNumSnapshot {
+      broadcast_seq: self.broadcast_seq_counter.load(Ordering::SeqCst),
+      ack_seq: self.ack_seq_counter.load(Ordering::SeqCst),
+      miss_update_count: self.miss_update_counter.load(Ordering::SeqCst),
+    }
+  }
+
+  /// Resets all the counters. Used when the server asks the client to discard its sync state.
+  pub fn reset(&self) {
+    self.broadcast_seq_counter.store(0, Ordering::SeqCst);
+    self.ack_seq_counter.store(0, Ordering::SeqCst);
+    self.miss_update_counter.store(0, Ordering::SeqCst);
+  }
+
   pub fn store_ack_seq_num(&self, seq_num: u32) -> u32 {
     // If the broadcast sequence counter is 0, set it to the current sequence number.
     if self.broadcast_seq_counter.load(Ordering::SeqCst) == 0 {
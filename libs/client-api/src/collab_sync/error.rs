@@ -36,6 +36,12 @@ pub enum SyncError {
   #[error("Can not apply update")]
   CannotApplyUpdate,
 
+  #[error("Server requires a force resync")]
+  ForceResync,
+
+  #[error("Collab has been deleted")]
+  CollabDeleted,
+
   #[error("{0}")]
   OverrideWithIncorrectData(String),
 
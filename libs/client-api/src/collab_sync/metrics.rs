@@ -0,0 +1,41 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counts the sync errors of a collab that make the client resync with the server. It only
+/// observes the errors and doesn't change how they are handled.
+#[derive(Debug, Default)]
+pub struct SyncMetrics {
+  miss_updates: AtomicU64,
+  cannot_apply_update: AtomicU64,
+  override_with_incorrect_data: AtomicU64,
+}
+
+impl SyncMetrics {
+  /// The number of times missing updates were detected, either by the client or by the server.
+  pub fn miss_updates_count(&self) -> u64 {
+    self.miss_updates.load(Ordering::Relaxed)
+  }
+
+  /// The number of times the server could not apply the updates of the client.
+  pub fn cannot_apply_update_count(&self) -> u64 {
+    self.cannot_apply_update.load(Ordering::Relaxed)
+  }
+
+  /// The number of times the server sent data that would override the collab with incorrect data.
+  pub fn override_with_incorrect_data_count(&self) -> u64 {
+    self.override_with_incorrect_data.load(Ordering::Relaxed)
+  }
+
+  pub(crate) fn incr_miss_updates(&self) {
+    self.miss_updates.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub(crate) fn incr_cannot_apply_update(&self) {
+    self.cannot_apply_update.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub(crate) fn incr_override_with_incorrect_data(&self) {
+    self
+      .override_with_incorrect_data
+      .fetch_add(1, Ordering::Relaxed);
+  }
+}
@@ -2,13 +2,19 @@ mod channel;
 mod collab_sink;
 mod collab_stream;
 mod error;
+mod metrics;
 mod period_state_check;
 mod plugin;
 mod sync_control;
+mod watermark;
 
 pub use channel::*;
 pub use collab_rt_entity::{MsgId, ServerCollabMessage};
+pub use collab_rt_protocol::UpdateEncoderVersion;
 pub use collab_sink::*;
+pub use collab_stream::{SeqNumSnapshot, SubscribeStream};
 pub use error::*;
+pub use metrics::*;
 pub use plugin::*;
 pub use sync_control::*;
+pub use watermark::*;
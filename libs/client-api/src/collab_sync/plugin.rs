@@ -1,4 +1,7 @@
-use crate::collab_sync::{CollabSyncState, SinkConfig, SyncControl, SyncReason};
+use crate::collab_sync::{
+  CollabSyncState, SinkConfig, StreamConfig, SubscribeStream, SyncControl, SyncProgress,
+  SyncReason, SyncWatermarkStore,
+};
 
 use crate::af_spawn;
 use crate::ws::{ConnectState, WSConnectStateReceiver};
@@ -64,8 +67,11 @@ where
     sink: Sink,
     sink_config: SinkConfig,
     stream: Stream,
+    subscribe_stream: Option<SubscribeStream<Stream>>,
+    stream_config: StreamConfig,
     channel: Option<Arc<C>>,
     mut ws_connect_state: WSConnectStateReceiver,
+    watermark_store: Option<Arc<dyn SyncWatermarkStore>>,
   ) -> Self {
     let sync_queue = SyncControl::new(
       object.clone(),
@@ -73,7 +79,10 @@ where
       sink,
       sink_config,
       stream,
+      subscribe_stream,
+      stream_config,
       collab.clone(),
+      watermark_store,
     );
 
     if let Some(local_collab) = collab.upgrade() {
@@ -134,6 +143,21 @@ where
       is_destroyed: Arc::new(Default::default()),
     }
   }
+
+  /// Subscribes to the force resync signals sent by the server for this collab.
+  pub fn subscribe_force_resync(&self) -> tokio::sync::broadcast::Receiver<String> {
+    self.sync_queue.subscribe_force_resync()
+  }
+
+  /// Subscribes to the deleted signal sent by the server when this collab is deleted.
+  pub fn subscribe_collab_deleted(&self) -> tokio::sync::broadcast::Receiver<String> {
+    self.sync_queue.subscribe_collab_deleted()
+  }
+
+  /// Subscribes to the progress of the updates received from the server for this collab.
+  pub fn subscribe_sync_progress(&self) -> tokio::sync::broadcast::Receiver<SyncProgress> {
+    self.sync_queue.subscribe_sync_progress()
+  }
 }
 
 impl<E, Sink, Stream, C> CollabPlugin for SyncPlugin<Sink, Stream, C>
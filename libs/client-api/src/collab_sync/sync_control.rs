@@ -1,8 +1,10 @@
 use crate::af_spawn;
-use crate::collab_sync::collab_stream::ObserveCollab;
+use crate::collab_sync::collab_stream::{
+  ObserveCollab, SeqNumCounter, SeqNumSnapshot, SubscribeStream,
+};
 use crate::collab_sync::{
   CollabSink, CollabSinkRunner, CollabSyncState, MissUpdateReason, SinkSignal, SyncError,
-  SyncObject,
+  SyncMetrics, SyncObject, SyncWatermark, SyncWatermarkStore,
 };
 
 use collab::core::awareness::Awareness;
@@ -10,10 +12,13 @@ use collab::core::collab::MutexCollab;
 use collab::core::origin::CollabOrigin;
 use collab::preclude::Collab;
 use collab_rt_entity::{ClientCollabMessage, InitSync, ServerCollabMessage, UpdateSync};
-use collab_rt_protocol::{ClientSyncProtocol, CollabSyncProtocol, Message, SyncMessage};
+use collab_rt_protocol::{
+  ClientSyncProtocol, CollabSyncProtocol, CustomMessage, Message, SyncMessage, UpdateEncoderVersion,
+};
 use futures_util::{SinkExt, StreamExt};
 use std::fmt::Display;
 use std::ops::Deref;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 use tokio::sync::{broadcast, watch};
@@ -34,9 +39,12 @@ pub struct SyncControl<Sink, Stream> {
   sink: Arc<CollabSink<Sink>>,
   /// The [ObserveCollab] will be spawned in a separate task It continuously receive
   /// the updates from the remote.
-  #[allow(dead_code)]
   observe_collab: ObserveCollab<Sink, Stream>,
   sync_state_tx: broadcast::Sender<CollabSyncState>,
+  force_resync_tx: broadcast::Sender<String>,
+  collab_deleted_tx: broadcast::Sender<String>,
+  sync_progress_tx: broadcast::Sender<SyncProgress>,
+  watermark_store: Option<Arc<dyn SyncWatermarkStore>>,
 }
 
 impl<Sink, Stream> Drop for SyncControl<Sink, Stream> {
@@ -59,11 +67,17 @@ where
     sink: Sink,
     sink_config: SinkConfig,
     stream: Stream,
+    subscribe_stream: Option<SubscribeStream<Stream>>,
+    stream_config: StreamConfig,
     collab: Weak<MutexCollab>,
+    watermark_store: Option<Arc<dyn SyncWatermarkStore>>,
   ) -> Self {
     let protocol = ClientSyncProtocol;
     let (notifier, notifier_rx) = watch::channel(SinkSignal::Proceed);
     let (sync_state_tx, _) = broadcast::channel(10);
+    let (force_resync_tx, _) = broadcast::channel(10);
+    let (collab_deleted_tx, _) = broadcast::channel(1);
+    let (sync_progress_tx, _) = broadcast::channel(100);
     debug_assert!(origin.client_user_id().is_some());
 
     // Create the sink and start the sink runner.
@@ -84,16 +98,36 @@ where
       origin.clone(),
       object.clone(),
       stream,
+      subscribe_stream,
+      stream_config,
       collab.clone(),
       Arc::downgrade(&sink),
+      force_resync_tx.clone(),
+      collab_deleted_tx.clone(),
+      sync_progress_tx.clone(),
     );
 
+    if let Some(watermark_store) = watermark_store.clone() {
+      af_spawn(store_watermark_when_synced(
+        object.object_id.clone(),
+        watermark_store,
+        sync_state_tx.subscribe(),
+        collab.clone(),
+        Arc::downgrade(&sink),
+        stream.seq_num_counter.clone(),
+      ));
+    }
+
     Self {
       object,
       origin,
       sink,
       observe_collab: stream,
       sync_state_tx,
+      force_resync_tx,
+      collab_deleted_tx,
+      sync_progress_tx,
+      watermark_store,
     }
   }
 
@@ -115,8 +149,55 @@ where
     self.sync_state_tx.subscribe()
   }
 
+  /// Subscribes to the force resync signals sent by the server. The object id of the collab is
+  /// sent to the receiver before the init sync is queued.
+  pub fn subscribe_force_resync(&self) -> broadcast::Receiver<String> {
+    self.force_resync_tx.subscribe()
+  }
+
+  /// Subscribes to the deleted signal sent by the server when the collab is deleted. The object
+  /// id of the collab is sent to the receiver, and the collab stops syncing afterwards.
+  pub fn subscribe_collab_deleted(&self) -> broadcast::Receiver<String> {
+    self.collab_deleted_tx.subscribe()
+  }
+
+  /// Subscribes to the progress of the updates received from the server. A [SyncProgress] is
+  /// sent each time a SyncStep2 of a server message is applied.
+  pub fn subscribe_sync_progress(&self) -> broadcast::Receiver<SyncProgress> {
+    self.sync_progress_tx.subscribe()
+  }
+
+  /// Subscribes to the state of receiving the changes from the remote. Unlike
+  /// [Self::subscribe_sync_state], which tracks the messages sent to the remote, this tells
+  /// whether the collab is up to date with the remote.
+  pub fn subscribe_remote_sync_state(&self) -> watch::Receiver<RemoteSyncState> {
+    self.observe_collab.subscribe_remote_sync_state()
+  }
+
+  /// Returns the counters of the sync errors of the collab, which can be reported to tell how
+  /// often the collab had to resync with the server.
+  pub fn sync_metrics(&self) -> Arc<SyncMetrics> {
+    self.observe_collab.metrics()
+  }
+
+  pub fn seq_status(&self) -> SeqNumSnapshot {
+    self.observe_collab.seq_status()
+  }
+
   /// Returns bool indicating whether the init sync is queued.
+  ///
+  /// If a [SyncWatermark] of the collab was stored, the collab is initialized or resumed by
+  /// syncing the updates since the watermark instead of performing a full init sync.
   pub fn init_sync(&self, collab: &Collab, reason: SyncReason) -> Result<bool, SyncError> {
+    let reason = match reason {
+      SyncReason::CollabInitialize | SyncReason::NetworkResume => {
+        match self.load_watermark_state_vector() {
+          Some(state_vector_v1) => SyncReason::ResumeFromWatermark { state_vector_v1 },
+          None => reason,
+        }
+      },
+      reason => reason,
+    };
     start_sync(
       self.origin.clone(),
       &self.object,
@@ -125,6 +206,87 @@ where
       reason,
     )
   }
+
+  fn load_watermark_state_vector(&self) -> Option<Vec<u8>> {
+    let watermark = self
+      .watermark_store
+      .as_ref()?
+      .load(&self.object.object_id)?;
+    // A watermark that can't be decoded is ignored, and the full init sync is performed.
+    StateVector::decode_v1(&watermark.state_vector_v1).ok()?;
+    Some(watermark.state_vector_v1)
+  }
+}
+
+/// Stores the [SyncWatermark] of the collab each time all the local updates are acknowledged by
+/// the server.
+async fn store_watermark_when_synced<E, Sink>(
+  object_id: String,
+  watermark_store: Arc<dyn SyncWatermarkStore>,
+  mut sync_state_rx: broadcast::Receiver<CollabSyncState>,
+  weak_collab: Weak<MutexCollab>,
+  weak_sink: Weak<CollabSink<Sink>>,
+  seq_num_counter: Arc<SeqNumCounter>,
+) where
+  E: Into<anyhow::Error> + Send + Sync + 'static,
+  Sink: SinkExt<Vec<ClientCollabMessage>, Error = E> + Send + Sync + Unpin + 'static,
+{
+  loop {
+    match sync_state_rx.recv().await {
+      Ok(CollabSyncState::Finished) => {},
+      Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+      Err(broadcast::error::RecvError::Closed) => break,
+    }
+
+    let (Some(collab), Some(sink)) = (weak_collab.upgrade(), weak_sink.upgrade()) else {
+      break;
+    };
+    // The local updates are queued while the collab is locked, so the state vector only covers
+    // the acknowledged updates if the queue is still empty while holding the lock.
+    if let Some(collab) = collab.try_lock() {
+      if sink.is_all_acked() {
+        let state_vector_v1 = collab.transact().state_vector().encode_v1();
+        watermark_store.store(SyncWatermark {
+          object_id: object_id.clone(),
+          ack_seq: seq_num_counter.ack_seq_counter.load(Ordering::SeqCst),
+          state_vector_v1,
+        });
+      }
+    }
+  }
+}
+
+/// The progress of applying a server message that carries SyncStep2 updates, such as the reply
+/// of an init sync.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SyncProgress {
+  pub object_id: String,
+  /// The number of SyncStep2 updates of the message applied so far.
+  pub applied_updates: u32,
+  /// The number of update bytes of the message applied so far.
+  pub applied_bytes: usize,
+  /// The length of the message payload.
+  pub total_bytes: usize,
+}
+
+/// The state of receiving the changes of a collab from the remote, as observed by the stream.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RemoteSyncState {
+  pub object_id: String,
+  pub status: RemoteSyncStatus,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RemoteSyncStatus {
+  /// The collab is doing a full sync with the remote, either on start or because the remote asked
+  /// for it.
+  InitSync,
+  /// Missing updates were detected and are being pulled from the remote.
+  PullingMissingUpdates,
+  /// The remote replied to the last request of the collab and no update is known to be missing.
+  Synced,
+  /// The stream or a message of the remote returned an error.
+  Errored,
 }
 
 pub enum SyncReason {
@@ -134,7 +296,12 @@ pub enum SyncReason {
     reason: MissUpdateReason,
   },
   ServerCannotApplyUpdate,
+  ServerForceResync,
   NetworkResume,
+  /// Syncs the updates made since the stored [SyncWatermark].
+  ResumeFromWatermark {
+    state_vector_v1: Vec<u8>,
+  },
 }
 
 impl Display for SyncReason {
@@ -143,7 +310,9 @@ impl Display for SyncReason {
       SyncReason::CollabInitialize => write!(f, "CollabInitialize"),
       SyncReason::MissUpdates { reason, .. } => write!(f, "MissUpdates: {}", reason),
       SyncReason::ServerCannotApplyUpdate => write!(f, "ServerCannotApplyUpdate"),
+      SyncReason::ServerForceResync => write!(f, "ServerForceResync"),
       SyncReason::NetworkResume => write!(f, "NetworkResume"),
+      SyncReason::ResumeFromWatermark { .. } => write!(f, "ResumeFromWatermark"),
     }
   }
 }
@@ -152,9 +321,33 @@ fn gen_sync_state<P: CollabSyncProtocol>(
   awareness: &Awareness,
   protocol: &P,
   sync_before: bool,
+  encoder_version: UpdateEncoderVersion,
 ) -> Result<Vec<u8>, SyncError> {
   let mut encoder = EncoderV1::new();
   protocol.start(awareness, &mut encoder, sync_before)?;
+  // Ask the server for the encoder version. Nothing is sent for v1, which every server uses by
+  // default, so that the servers that don't support the negotiation can still parse the payload.
+  if encoder_version != UpdateEncoderVersion::V1 {
+    Message::Custom(CustomMessage::EncoderVersion(encoder_version)).encode(&mut encoder);
+  }
+  Ok(encoder.to_vec())
+}
+
+/// Generates the payload of a sync from a [SyncWatermark]. The [SyncMessage::SyncStep1] asks the
+/// server for the updates the client is missing, and the [SyncMessage::Update] sends the local
+/// updates made since the watermark. The server replies the missing updates in the ack.
+fn gen_watermark_sync(collab: &Collab, watermark_sv: StateVector) -> Result<Vec<u8>, SyncError> {
+  let (state_vector, update) = {
+    let txn = collab.transact();
+    (
+      txn.state_vector(),
+      txn.encode_state_as_update_v1(&watermark_sv),
+    )
+  };
+
+  let mut encoder = EncoderV1::new();
+  Message::Sync(SyncMessage::SyncStep1(state_vector)).encode(&mut encoder);
+  Message::Sync(SyncMessage::Update(update)).encode(&mut encoder);
   Ok(encoder.to_vec())
 }
 
@@ -204,7 +397,12 @@ where
           reason
         );
         let awareness = collab.get_awareness();
-        let payload = gen_sync_state(awareness, &ClientSyncProtocol, sync_before)?;
+        let payload = gen_sync_state(
+          awareness,
+          &ClientSyncProtocol,
+          sync_before,
+          sink.encoder_version(),
+        )?;
         sink.queue_init_sync(|msg_id| {
           let init_sync = InitSync::new(
             origin,
@@ -235,8 +433,28 @@ where
         });
       },
     },
+    SyncReason::ResumeFromWatermark {
+      ref state_vector_v1,
+    } => {
+      trace!(
+        "🔥{} start watermark sync, reason: {}",
+        &sync_object.object_id,
+        reason
+      );
+      let payload = gen_watermark_sync(collab, StateVector::decode_v1(state_vector_v1)?)?;
+      sink.queue_msg(|msg_id| {
+        let update_sync = UpdateSync::new(
+          origin.clone(),
+          sync_object.object_id.clone(),
+          payload,
+          msg_id,
+        );
+        ClientCollabMessage::new_update_sync(update_sync)
+      });
+    },
     SyncReason::CollabInitialize
     | SyncReason::ServerCannotApplyUpdate
+    | SyncReason::ServerForceResync
     | SyncReason::NetworkResume => {
       trace!(
         "🔥{} start init sync, reason: {}",
@@ -244,7 +462,12 @@ where
         reason
       );
       let awareness = collab.get_awareness();
-      let payload = gen_sync_state(awareness, &ClientSyncProtocol, sync_before)?;
+      let payload = gen_sync_state(
+        awareness,
+        &ClientSyncProtocol,
+        sync_before,
+        sink.encoder_version(),
+      )?;
 
       sink.queue_init_sync(|msg_id| {
         let init_sync = InitSync::new(
@@ -277,6 +500,10 @@ pub struct SinkConfig {
   pub send_timeout: Duration,
   /// `maximum_payload_size` is the maximum size of the messages to be merged.
   pub maximum_payload_size: usize,
+  /// `encoder_version` is the encoder version of the updates that the client asks the server for
+  /// during the init sync. Only set it above v1 for the servers that support the negotiation,
+  /// the others fail to parse the init sync.
+  pub encoder_version: UpdateEncoderVersion,
 }
 
 impl SinkConfig {
@@ -287,6 +514,10 @@ impl SinkConfig {
     self.send_timeout = Duration::from_secs(secs);
     self
   }
+  pub fn encoder_version(mut self, version: UpdateEncoderVersion) -> Self {
+    self.encoder_version = version;
+    self
+  }
 }
 
 impl Default for SinkConfig {
@@ -294,6 +525,73 @@ impl Default for SinkConfig {
     Self {
       send_timeout: Duration::from_secs(DEFAULT_SYNC_TIMEOUT),
       maximum_payload_size: 1024 * 10,
+      encoder_version: UpdateEncoderVersion::V1,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct StreamConfig {
+  /// `reconnect_delay` is the time to wait before receiving from the stream again after it yields
+  /// an error. The delay doubles on each consecutive error, up to `max_reconnect_delay`.
+  pub reconnect_delay: Duration,
+  pub max_reconnect_delay: Duration,
+  /// `max_reconnect_attempts` is the number of consecutive errors after which the stream stops
+  /// receiving the changes from the remote.
+  pub max_reconnect_attempts: u32,
+  /// `pull_missing_updates_delay` is the time to wait before pulling the missing updates from the
+  /// remote when missing updates are detected. The pending pull is cancelled if other missing
+  /// updates are detected in the meantime.
+  pub pull_missing_updates_delay: Duration,
+  /// `catch_apply_panic` applies the remote updates on a separate task, so that a panic while
+  /// applying an update doesn't bring down the stream. Disable it to apply the updates inline when
+  /// the yrs version in use is known not to panic.
+  pub catch_apply_panic: bool,
+}
+
+impl StreamConfig {
+  pub fn new() -> Self {
+    Self::default()
+  }
+  pub fn reconnect_delay(mut self, delay: Duration) -> Self {
+    self.reconnect_delay = delay;
+    self
+  }
+  pub fn max_reconnect_delay(mut self, delay: Duration) -> Self {
+    self.max_reconnect_delay = delay;
+    self
+  }
+  pub fn max_reconnect_attempts(mut self, attempts: u32) -> Self {
+    self.max_reconnect_attempts = attempts;
+    self
+  }
+  pub fn pull_missing_updates_delay(mut self, delay: Duration) -> Self {
+    self.pull_missing_updates_delay = delay;
+    self
+  }
+  pub fn catch_apply_panic(mut self, catch_apply_panic: bool) -> Self {
+    self.catch_apply_panic = catch_apply_panic;
+    self
+  }
+
+  /// Returns the delay before the given reconnect attempt, starting from 1.
+  pub(crate) fn reconnect_delay_for_attempt(&self, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    self
+      .reconnect_delay
+      .saturating_mul(factor)
+      .min(self.max_reconnect_delay)
+  }
+}
+
+impl Default for StreamConfig {
+  fn default() -> Self {
+    Self {
+      reconnect_delay: Duration::from_secs(1),
+      max_reconnect_delay: Duration::from_secs(30),
+      max_reconnect_attempts: 5,
+      pull_missing_updates_delay: Duration::from_secs(3),
+      catch_apply_panic: true,
     }
   }
 }
@@ -0,0 +1,19 @@
+/// How far a collab was synced with the server. It's persisted by the embedder through the
+/// [SyncWatermarkStore], so that after a restart the client can sync the updates made since the
+/// watermark instead of performing a full init sync.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SyncWatermark {
+  pub object_id: String,
+  /// The sequence number of the last message acknowledged by the server.
+  pub ack_seq: u32,
+  /// The state vector of the collab, encoded with v1, when all the local updates were
+  /// acknowledged by the server.
+  pub state_vector_v1: Vec<u8>,
+}
+
+/// Persists the [SyncWatermark] of the collabs. The watermark is stored each time all the local
+/// updates of a collab are acknowledged by the server, and loaded when the collab starts syncing.
+pub trait SyncWatermarkStore: Send + Sync {
+  fn load(&self, object_id: &str) -> Option<SyncWatermark>;
+  fn store(&self, watermark: SyncWatermark);
+}
@@ -5,7 +5,7 @@ use client_api_entity::CollabType;
 use gotrue::grant::PasswordGrant;
 use gotrue::grant::{Grant, RefreshTokenGrant};
 use gotrue::params::MagicLinkParams;
-use gotrue::params::{AdminUserParams, GenerateLinkParams};
+use gotrue::params::{AdminUserParams, GenerateLinkParams, ResendParams, ResendType};
 use shared_entity::dto::workspace_dto::{CreateWorkspaceParam, PatchWorkspaceParam};
 use std::fmt::{Display, Formatter};
 #[cfg(feature = "enable_brotli")]
@@ -36,6 +36,7 @@ use client_api_entity::{GotrueTokenResponse, UpdateGotrueUserParams, User};
 use shared_entity::dto::ai_dto::AIModel;
 
 pub const X_COMPRESSION_TYPE: &str = "X-Compression-Type";
+pub const X_IDEMPOTENCY_KEY: &str = "Idempotency-Key";
 pub const X_COMPRESSION_BUFFER_SIZE: &str = "X-Compression-Buffer-Size";
 pub const X_COMPRESSION_TYPE_BROTLI: &str = "brotli";
 
@@ -240,6 +241,22 @@ impl Client {
     Ok(())
   }
 
+  /// Sends the sign up confirmation email again, for users that can not sign in because their
+  /// email is not confirmed yet. gotrue limits how often the email can be sent.
+  ///
+  /// Returns `ErrorCode::EmailAlreadyConfirmed` if the email is already confirmed.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn resend_confirmation_email(&self, email: &str) -> Result<(), AppResponseError> {
+    self
+      .gotrue_client
+      .resend(&ResendParams {
+        type_: ResendType::Signup,
+        email: email.to_owned(),
+      })
+      .await?;
+    Ok(())
+  }
+
   /// Attempts to sign in using a URL, extracting refresh_token from the URL.
   /// It looks like, e.g., `appflowy-flutter://#access_token=...&expires_in=3600&provider_token=...&refresh_token=...&token_type=bearer`.
   ///
@@ -626,6 +643,24 @@ impl Client {
       .into_data()
   }
 
+  /// Asks every client that is currently editing a collab of the workspace to discard its sync
+  /// state and perform a fresh init sync. Only the owner of the workspace can call this.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn force_resync_workspace(&self, workspace_id: &str) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/force-resync",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&())
+      .send()
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
   #[instrument(skip_all, err)]
   pub async fn sign_in_password(
     &self,
@@ -1,18 +1,46 @@
+use app_error::AppError;
 use bytes::Bytes;
-use client_api_entity::{PublishInfo, UpdatePublishNamespace};
-use reqwest::Method;
+use client_api_entity::{
+  PublishAuditLogEntry, PublishInfo, PublishNamespaceInvalidReason, PublishNamespaceRules,
+  PublishOgMetadata, PublishVersion, PublishedDatabase, PublishedViewOfUser, PublishedViewStats,
+  PublishedViewSummary, RepointPublishedView, UnpublishViewResult, UpdatePublishName,
+  UpdatePublishNamespace,
+};
+use reqwest::header::{ACCEPT_LANGUAGE, ETAG, IF_NONE_MATCH};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use shared_entity::response::{AppResponse, AppResponseError, ErrorCode};
+use std::borrow::Cow;
 use tracing::instrument;
-use shared_entity::response::{AppResponse, AppResponseError};
 
 use crate::Client;
 
+/// The error of [Client::set_workspace_publish_namespace]. When the namespace is rejected, the
+/// code is [ErrorCode::InvalidRequest] and `invalid_reason` tells the user what to fix.
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("code:{code:?} msg: {message}")]
+pub struct SetPublishNamespaceError {
+  pub code: ErrorCode,
+  pub message: Cow<'static, str>,
+  pub invalid_reason: Option<PublishNamespaceInvalidReason>,
+}
+
+impl From<AppResponseError> for SetPublishNamespaceError {
+  fn from(err: AppResponseError) -> Self {
+    Self {
+      code: err.code,
+      message: err.message,
+      invalid_reason: None,
+    }
+  }
+}
+
 // Publisher API
 impl Client {
   pub async fn set_workspace_publish_namespace(
     &self,
     workspace_id: &str,
     new_namespace: &str,
-  ) -> Result<(), AppResponseError> {
+  ) -> Result<(), SetPublishNamespaceError> {
     let url = format!(
       "{}/api/workspace/{}/publish-namespace",
       self.base_url, workspace_id
@@ -25,9 +53,20 @@ impl Client {
         new_namespace: new_namespace.to_string(),
       })
       .send()
-      .await?;
+      .await
+      .map_err(AppResponseError::from)?;
 
-    AppResponse::<()>::from_response(resp).await?.into_error()
+    let resp = AppResponse::<PublishNamespaceInvalidReason>::from_response(resp)
+      .await
+      .map_err(AppResponseError::from)?;
+    if resp.is_ok() {
+      return Ok(());
+    }
+    Err(SetPublishNamespaceError {
+      code: resp.code,
+      message: resp.message,
+      invalid_reason: resp.data,
+    })
   }
 
   pub async fn get_workspace_publish_namespace(
@@ -48,11 +87,13 @@ impl Client {
       .into_data()
   }
 
+  /// Unpublishes the views. Each view is processed independently, and the result of each view is
+  /// returned in the same order as `view_ids`.
   pub async fn unpublish_collabs(
     &self,
     workspace_id: &str,
     view_ids: &[uuid::Uuid],
-  ) -> Result<(), AppResponseError> {
+  ) -> Result<Vec<UnpublishViewResult>, AppResponseError> {
     let url = format!("{}/api/workspace/{}/publish", self.base_url, workspace_id);
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
@@ -60,20 +101,251 @@ impl Client {
       .json(view_ids)
       .send()
       .await?;
+    AppResponse::<Vec<UnpublishViewResult>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Unpublishes all the published views of the workspace. Returns the ids of the unpublished
+  /// views. Only the owner of the workspace can unpublish all the views.
+  pub async fn unpublish_all_collabs(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<uuid::Uuid>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/all",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<Vec<uuid::Uuid>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Switches the view backing the published collab named `publish_name` to `new_view_id`,
+  /// keeping the public url unchanged.
+  pub async fn repoint_published_view(
+    &self,
+    workspace_id: &str,
+    publish_name: &str,
+    new_view_id: uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/repoint",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&RepointPublishedView {
+        publish_name: publish_name.to_string(),
+        new_view_id,
+      })
+      .send()
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Changes the publish name, and therefore the public url, of a published view. The view stays
+  /// published.
+  pub async fn set_publish_name(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    new_publish_name: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/name",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdatePublishName {
+        new_publish_name: new_publish_name.to_string(),
+      })
+      .send()
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the changes to the publish settings of the view, oldest first. Only the owner of
+  /// the workspace can read it.
+  pub async fn get_publish_audit_log(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+  ) -> Result<Vec<PublishAuditLogEntry>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/audit-log",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<Vec<PublishAuditLogEntry>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns how many times the published view was read. Only the owner of the workspace can
+  /// read it. When `exclude_owner` is true, the reads of the owner and of the publisher are not
+  /// counted.
+  pub async fn get_published_view_stats(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    exclude_owner: bool,
+  ) -> Result<PublishedViewStats, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/stats",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&[("exclude_owner", exclude_owner)])
+      .send()
+      .await?;
+    AppResponse::<PublishedViewStats>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the versions of the published view that can be restored, newest first.
+  pub async fn list_publish_versions(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+  ) -> Result<Vec<PublishVersion>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/versions",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<Vec<PublishVersion>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Restores the published view to one of the versions returned by [Self::list_publish_versions].
+  /// Only the owner of the workspace or the publisher of the view can restore it.
+  /// `latest_version` is the newest version returned by [Self::list_publish_versions]. When the
+  /// view was published again since, the restore fails with `ErrorCode::PublishVersionConflict`.
+  pub async fn restore_publish_version(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    version: i64,
+    latest_version: i64,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/versions/{}/restore",
+      self.base_url, workspace_id, view_id, version
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .query(&[("latest_version", latest_version)])
+      .send()
+      .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
+
+  /// Returns the databases of the workspace that have at least one published view, along with
+  /// their published views.
+  pub async fn list_workspace_published_databases(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<PublishedDatabase>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/databases",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<Vec<PublishedDatabase>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the views published by the current user across all workspaces, most recently
+  /// published first.
+  pub async fn get_my_published_views(&self) -> Result<Vec<PublishedViewOfUser>, AppResponseError> {
+    let url = format!("{}/api/workspace/my-published-views", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    AppResponse::<Vec<PublishedViewOfUser>>::from_response(resp)
+      .await?
+      .into_data()
+  }
 }
 
 // Guest API (no login required)
 impl Client {
+  /// Returns the rules the server applies to publish namespaces, see
+  /// [Client::set_workspace_publish_namespace].
+  pub async fn get_publish_namespace_rules(
+    &self,
+  ) -> Result<PublishNamespaceRules, AppResponseError> {
+    let url = format!("{}/api/workspace/publish-namespace-rules", self.base_url);
+    let resp = self.cloud_client.get(&url).send().await?;
+    AppResponse::<PublishNamespaceRules>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "debug", skip_all)]
   pub async fn get_published_collab_info(
     &self,
     view_id: &uuid::Uuid,
+  ) -> Result<PublishInfo, AppResponseError> {
+    self
+      .get_published_collab_info_with_lang(view_id, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_collab_info], but the returned title is resolved from the
+  /// `localized_titles` of the publish metadata using the given `Accept-Language` value.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_info_localized(
+    &self,
+    view_id: &uuid::Uuid,
+    accept_language: &str,
+  ) -> Result<PublishInfo, AppResponseError> {
+    self
+      .get_published_collab_info_with_lang(view_id, Some(accept_language))
+      .await
+  }
+
+  async fn get_published_collab_info_with_lang(
+    &self,
+    view_id: &uuid::Uuid,
+    accept_language: Option<&str>,
   ) -> Result<PublishInfo, AppResponseError> {
     let url = format!("{}/api/workspace/published-info/{}", self.base_url, view_id,);
 
-    let resp = self.cloud_client.get(&url).send().await?;
+    let mut builder = self.published_view_request(&url);
+    if let Some(accept_language) = accept_language {
+      builder = builder.header(ACCEPT_LANGUAGE, accept_language);
+    }
+    let resp = builder.send().await?;
     AppResponse::<PublishInfo>::from_response(resp)
       .await?
       .into_data()
@@ -88,18 +360,70 @@ impl Client {
   where
     T: serde::de::DeserializeOwned,
   {
-    tracing::debug!("get_published_collab: {} {}", publish_namespace, publish_name);
+    self
+      .get_published_collab_with_lang(publish_namespace, publish_name, None)
+      .await
+  }
+
+  /// Same as [Client::get_published_collab], but the `title` of the metadata is replaced by the
+  /// entry of `localized_titles` that best matches the given `Accept-Language` value.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_localized<T>(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    accept_language: &str,
+  ) -> Result<T, AppResponseError>
+  where
+    T: serde::de::DeserializeOwned,
+  {
+    self
+      .get_published_collab_with_lang(publish_namespace, publish_name, Some(accept_language))
+      .await
+  }
+
+  /// Same as [Client::get_published_collab], but also returns the raw metadata. Fields that are
+  /// unknown to `T` are dropped from the typed value but kept in the raw one.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_with_raw<T>(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+  ) -> Result<(T, serde_json::Value), AppResponseError>
+  where
+    T: serde::de::DeserializeOwned,
+  {
+    let raw = self
+      .get_published_collab_with_lang::<serde_json::Value>(publish_namespace, publish_name, None)
+      .await?;
+    let meta = serde_json::from_value::<T>(raw.clone())?;
+    Ok((meta, raw))
+  }
+
+  async fn get_published_collab_with_lang<T>(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    accept_language: Option<&str>,
+  ) -> Result<T, AppResponseError>
+  where
+    T: serde::de::DeserializeOwned,
+  {
+    tracing::debug!(
+      "get_published_collab: {} {}",
+      publish_namespace,
+      publish_name
+    );
     let url = format!(
       "{}/api/workspace/published/{}/{}",
       self.base_url, publish_namespace, publish_name
     );
 
-    let resp = self
-      .cloud_client
-      .get(&url)
-      .send()
-      .await?
-      .error_for_status()?;
+    let mut builder = self.published_view_request(&url);
+    if let Some(accept_language) = accept_language {
+      builder = builder.header(ACCEPT_LANGUAGE, accept_language);
+    }
+    let resp = builder.send().await?.error_for_status()?;
 
     let txt = resp.text().await?;
 
@@ -112,30 +436,123 @@ impl Client {
     Ok(meta)
   }
 
+  /// Returns the Open Graph preview (title, description and image) of a published view.
   #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_og_metadata(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+  ) -> Result<PublishOgMetadata, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/og",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self.published_view_request(&url).send().await?;
+    AppResponse::<PublishOgMetadata>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "debug", skip_all)]
+  /// Returns the published views of the namespace, most recently published first. Only the
+  /// stored publish metadata is returned, which is enough to render an index of the namespace.
+  pub async fn get_namespace_published_views(
+    &self,
+    publish_namespace: &str,
+  ) -> Result<Vec<PublishedViewSummary>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}",
+      self.base_url, publish_namespace
+    );
+    let resp = self.published_view_request(&url).send().await?;
+    AppResponse::<Vec<PublishedViewSummary>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Published views can be read by guests. When the client is logged in, the access token is
+  /// attached so that the views of workspaces that require login can be read as well.
+  fn published_view_request(&self, url: &str) -> RequestBuilder {
+    let builder = self.cloud_client.get(url);
+    match self.access_token() {
+      Ok(access_token) => builder.bearer_auth(access_token),
+      Err(_) => builder,
+    }
+  }
+
   pub async fn get_published_collab_blob(
     &self,
     publish_namespace: &str,
     publish_name: &str,
   ) -> Result<Bytes, AppResponseError> {
-    tracing::debug!("get_published_collab_blob: {} {}", publish_namespace, publish_name);
+    let (bytes, _etag) = self
+      .get_published_collab_blob_with_etag(publish_namespace, publish_name)
+      .await?;
+    Ok(bytes)
+  }
+
+  /// Same as [Client::get_published_collab_blob], but also returns the ETag of the blob, which
+  /// can be passed to [Client::get_published_collab_blob_if_changed].
+  pub async fn get_published_collab_blob_with_etag(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+  ) -> Result<(Bytes, Option<String>), AppResponseError> {
+    let blob = self
+      .send_published_collab_blob_request(publish_namespace, publish_name, None)
+      .await?;
+    // The server only responds with 304 when an ETag is sent
+    blob.ok_or_else(|| AppError::Unhandled("unexpected 304 response".to_string()).into())
+  }
+
+  /// Returns the blob and its new ETag if the blob changed since the ETag was returned, or `None`
+  /// if it didn't, in which case nothing is downloaded.
+  pub async fn get_published_collab_blob_if_changed(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    etag: &str,
+  ) -> Result<Option<(Bytes, Option<String>)>, AppResponseError> {
+    self
+      .send_published_collab_blob_request(publish_namespace, publish_name, Some(etag))
+      .await
+  }
+
+  async fn send_published_collab_blob_request(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    if_none_match: Option<&str>,
+  ) -> Result<Option<(Bytes, Option<String>)>, AppResponseError> {
+    tracing::debug!(
+      "get_published_collab_blob: {} {}",
+      publish_namespace,
+      publish_name
+    );
     let url = format!(
       "{}/api/workspace/published/{}/{}/blob",
       self.base_url, publish_namespace, publish_name
     );
-    let bytes = self
-      .cloud_client
-      .get(&url)
-      .send()
-      .await?
-      .error_for_status()?
-      .bytes()
-      .await?;
+    let mut builder = self.published_view_request(&url);
+    if let Some(etag) = if_none_match {
+      builder = builder.header(IF_NONE_MATCH, etag);
+    }
+    let resp = builder.send().await?;
+    if resp.status() == StatusCode::NOT_MODIFIED {
+      return Ok(None);
+    }
+    let resp = resp.error_for_status()?;
+    let etag = resp
+      .headers()
+      .get(ETAG)
+      .and_then(|value| value.to_str().ok())
+      .map(|value| value.to_string());
+    let bytes = resp.bytes().await?;
 
     if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
       return Err(app_err);
     }
 
-    Ok(bytes)
+    Ok(Some((bytes, etag)))
   }
 }
@@ -1,7 +1,7 @@
 use reqwest::Method;
 use tracing::{instrument, trace};
 
-use client_api_entity::AFWorkspaceSettings;
+use client_api_entity::{AFRole, AFWorkspaceSettings};
 use shared_entity::response::{AppResponse, AppResponseError};
 
 use crate::entity::AFWorkspaceSettingsChange;
@@ -51,4 +51,38 @@ impl Client {
     let resp = AppResponse::<AFWorkspaceSettings>::from_response(resp).await?;
     resp.into_data()
   }
+
+  /// Enables or disables publishing for the workspace. Only the owner of the workspace can change
+  /// it. While disabled, publishing and changing the publish namespace fail with
+  /// `ErrorCode::PublishDisabled`.
+  pub async fn set_workspace_publishing_enabled<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+    enabled: bool,
+  ) -> Result<(), AppResponseError> {
+    self
+      .update_workspace_settings(
+        workspace_id,
+        &AFWorkspaceSettingsChange::new().publishing_enabled(enabled),
+      )
+      .await?;
+    Ok(())
+  }
+
+  /// Sets the least privileged role that can publish and unpublish the views of the workspace.
+  /// Only the owner of the workspace can change it. Users with a less privileged role get
+  /// `ErrorCode::UserUnAuthorized` when publishing or unpublishing.
+  pub async fn set_workspace_min_publish_role<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+    min_publish_role: AFRole,
+  ) -> Result<(), AppResponseError> {
+    self
+      .update_workspace_settings(
+        workspace_id,
+        &AFWorkspaceSettingsChange::new().min_publish_role(min_publish_role),
+      )
+      .await?;
+    Ok(())
+  }
 }
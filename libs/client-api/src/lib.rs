@@ -31,6 +31,7 @@ mod http_settings;
 pub mod ws;
 
 pub mod error {
+  pub use crate::http_publish::SetPublishNamespaceError;
   pub use shared_entity::response::AppResponseError;
   pub use shared_entity::response::ErrorCode;
 }
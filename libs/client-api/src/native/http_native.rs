@@ -1,4 +1,4 @@
-use crate::http::log_request_id;
+use crate::http::{log_request_id, X_IDEMPOTENCY_KEY};
 use crate::native::GetCollabAction;
 use crate::ws::{ConnectInfo, WSClientConnectURLProvider, WSClientHttpSender, WSError};
 use crate::{spawn_blocking_brotli_compress, Client};
@@ -15,6 +15,7 @@ use client_api_entity::{
 use collab_rt_entity::HttpRealtimeMessage;
 use futures::Stream;
 use futures_util::stream;
+use futures_util::StreamExt;
 use prost::Message;
 use reqwest::{Body, Method};
 use serde::Serialize;
@@ -30,6 +31,7 @@ use std::time::Duration;
 use tokio_retry::strategy::{ExponentialBackoff, FixedInterval};
 use tokio_retry::{Retry, RetryIf};
 use tracing::{event, info, instrument, trace};
+use uuid::Uuid;
 
 pub use infra::file_util::ChunkedBytes;
 use shared_entity::dto::ai_dto::CompleteTextParams;
@@ -273,24 +275,100 @@ impl Client {
     }
   }
 
+  /// Publishes the items as a single batch. The batch is all-or-nothing: if the request fails or
+  /// is cancelled before the server responds, none of the items is published unless the server
+  /// already committed the whole batch.
+  /// The items whose `content_hash` matches the one of their current publish are skipped. Returns
+  /// the view ids of the items that were published.
   pub async fn publish_collabs<Metadata, Data>(
     &self,
     workspace_id: &str,
     items: Vec<PublishCollabItem<Metadata, Data>>,
-  ) -> Result<(), AppResponseError>
+  ) -> Result<Vec<Uuid>, AppResponseError>
   where
     Metadata: serde::Serialize + Send + 'static + Unpin,
     Data: AsRef<[u8]> + Send + 'static + Unpin,
   {
-    let publish_collab_stream = PublishCollabItemStream::new(items);
-    let url = format!("{}/api/workspace/{}/publish", self.base_url, workspace_id,);
+    self
+      .publish_collabs_with_optional_idempotency_key(workspace_id, items, None)
+      .await
+  }
+
+  /// Same as [Client::publish_collabs], but safe to retry: the server records the result of the
+  /// request with the idempotency key, and returns it to the requests sent again with the same
+  /// key instead of publishing the items again. Use a new key for each batch.
+  pub async fn publish_collabs_with_idempotency_key<Metadata, Data>(
+    &self,
+    workspace_id: &str,
+    items: Vec<PublishCollabItem<Metadata, Data>>,
+    idempotency_key: &str,
+  ) -> Result<Vec<Uuid>, AppResponseError>
+  where
+    Metadata: serde::Serialize + Send + 'static + Unpin,
+    Data: AsRef<[u8]> + Send + 'static + Unpin,
+  {
+    self
+      .publish_collabs_with_optional_idempotency_key(workspace_id, items, Some(idempotency_key))
+      .await
+  }
+
+  /// Publishes the items as they are produced by the stream, so that the whole batch doesn't have
+  /// to be kept in memory. The server publishes the items in bounded batches while reading them,
+  /// so unlike [Client::publish_collabs], the items published before a failure stay published.
+  /// Returns the view ids of the items that were published.
+  pub async fn publish_collabs_stream<Metadata, Data, S>(
+    &self,
+    workspace_id: &str,
+    items: S,
+  ) -> Result<Vec<Uuid>, AppResponseError>
+  where
+    Metadata: serde::Serialize + Send + Sync + 'static,
+    Data: AsRef<[u8]> + Send + Sync + 'static,
+    S: Stream<Item = PublishCollabItem<Metadata, Data>> + Send + Sync + 'static,
+  {
+    let publish_collab_stream = items
+      .map(|item| serialize_metadata_data(&item.meta, item.data.as_ref()))
+      .chain(stream::once(async {
+        Ok::<_, std::io::Error>(Bytes::from((0_u32).to_le_bytes().to_vec()))
+      }));
+    let url = format!(
+      "{}/api/workspace/{}/publish/stream",
+      self.base_url, workspace_id
+    );
     let resp = self
       .http_client_with_auth(Method::POST, &url)
       .await?
       .body(Body::wrap_stream(publish_collab_stream))
       .send()
       .await?;
-    AppResponse::<()>::from_response(resp).await?.into_error()
+    AppResponse::<Vec<Uuid>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  async fn publish_collabs_with_optional_idempotency_key<Metadata, Data>(
+    &self,
+    workspace_id: &str,
+    items: Vec<PublishCollabItem<Metadata, Data>>,
+    idempotency_key: Option<&str>,
+  ) -> Result<Vec<Uuid>, AppResponseError>
+  where
+    Metadata: serde::Serialize + Send + 'static + Unpin,
+    Data: AsRef<[u8]> + Send + 'static + Unpin,
+  {
+    let publish_collab_stream = PublishCollabItemStream::new(items);
+    let url = format!("{}/api/workspace/{}/publish", self.base_url, workspace_id,);
+    let mut builder = self.http_client_with_auth(Method::POST, &url).await?;
+    if let Some(idempotency_key) = idempotency_key {
+      builder = builder.header(X_IDEMPOTENCY_KEY, idempotency_key);
+    }
+    let resp = builder
+      .body(Body::wrap_stream(publish_collab_stream))
+      .send()
+      .await?;
+    AppResponse::<Vec<Uuid>>::from_response(resp)
+      .await?
+      .into_data()
   }
 }
 
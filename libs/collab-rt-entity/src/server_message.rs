@@ -211,6 +211,10 @@ pub enum AckCode {
   Internal = 3,
   EncodeStateAsUpdateFail = 4,
   MissUpdate = 5,
+  /// Sent by the server to ask the client to discard its sync state and perform a fresh init sync.
+  ForceResync = 6,
+  /// Sent by the server when the collab has been deleted. The client should stop syncing it.
+  CollabDeleted = 7,
 }
 
 impl From<u8> for AckCode {
@@ -222,6 +226,8 @@ impl From<u8> for AckCode {
       3 => AckCode::Internal,
       4 => AckCode::EncodeStateAsUpdateFail,
       5 => AckCode::MissUpdate,
+      6 => AckCode::ForceResync,
+      7 => AckCode::CollabDeleted,
       _ => AckCode::Internal,
     }
   }
@@ -266,7 +272,8 @@ pub struct BroadcastSync {
   pub origin: CollabOrigin,
   pub(crate) object_id: String,
   /// "The payload is encoded using the `EncoderV1` with the `Message` struct.
-  /// It can be parsed into: Message::Sync::(SyncMessage::Update(update))
+  /// It can be parsed into: Message::Sync::(SyncMessage::Update(update)), preceded by a
+  /// Message::Custom(CustomMessage::EncoderVersion(version)) when the update is not encoded with v1.
   pub(crate) payload: Bytes,
   pub seq_num: u32,
 }
@@ -6,7 +6,7 @@ use thiserror::Error;
 
 use yrs::updates::decoder::{Decode, Decoder};
 use yrs::updates::encoder::{Encode, Encoder};
-use yrs::StateVector;
+use yrs::{StateVector, Update};
 
 /// Tag id for [Message::Sync].
 pub const MSG_SYNC: u8 = 0;
@@ -98,16 +98,28 @@ impl Display for Message {
 
 /// Tag id for [CustomMessage::MSG_CUSTOM_START_SYNC].
 pub const MSG_CUSTOM_START_SYNC: u8 = 0;
+/// Tag id for [CustomMessage::EncoderVersion].
+pub const MSG_CUSTOM_ENCODER_VERSION: u8 = 1;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum CustomMessage {
   SyncCheck(SyncMeta),
+  /// Sent by the client at the end of the init sync with the encoder version it asks for. The
+  /// server replies with the version used to encode the updates it broadcasts to the client. The
+  /// servers that don't know this message fail to parse the payload, so the client only sends it
+  /// when it is configured to ask for a version above v1.
+  ///
+  /// The server also sends it at the start of a broadcast that is not encoded with v1. The
+  /// updates of a payload are decoded with the version of the last [CustomMessage::EncoderVersion]
+  /// that precedes them in the payload, or with v1 if there is none.
+  EncoderVersion(UpdateEncoderVersion),
 }
 
 impl Display for CustomMessage {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
       CustomMessage::SyncCheck(_) => f.write_str("SyncCheck"),
+      CustomMessage::EncoderVersion(version) => write!(f, "EncoderVersion({:?})", version),
     }
   }
 }
@@ -118,6 +130,10 @@ impl Encode for CustomMessage {
         encoder.write_var(MSG_CUSTOM_START_SYNC);
         encoder.write_buf(msg.to_vec());
       },
+      CustomMessage::EncoderVersion(version) => {
+        encoder.write_var(MSG_CUSTOM_ENCODER_VERSION);
+        encoder.write_var(*version as u8);
+      },
     }
   }
 }
@@ -131,6 +147,49 @@ impl Decode for CustomMessage {
         let meta = SyncMeta::from_vec(buf)?;
         Ok(CustomMessage::SyncCheck(meta))
       },
+      MSG_CUSTOM_ENCODER_VERSION => {
+        let version = UpdateEncoderVersion::try_from(decoder.read_var::<u8>()?)?;
+        Ok(CustomMessage::EncoderVersion(version))
+      },
+      _ => Err(yrs::encoding::read::Error::UnexpectedValue),
+    }
+  }
+}
+
+/// The encoding of the [yrs::Update]s carried by [SyncMessage::SyncStep2] and [SyncMessage::Update].
+/// [UpdateEncoderVersion::V1] is used unless the payload says otherwise with a
+/// [CustomMessage::EncoderVersion].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(u8)]
+pub enum UpdateEncoderVersion {
+  #[default]
+  V1 = 1,
+  V2 = 2,
+}
+
+impl UpdateEncoderVersion {
+  pub fn decode_update(&self, update: &[u8]) -> Result<Update, yrs::encoding::read::Error> {
+    match self {
+      UpdateEncoderVersion::V1 => Update::decode_v1(update),
+      UpdateEncoderVersion::V2 => Update::decode_v2(update),
+    }
+  }
+
+  pub fn encode_update(&self, update: &Update) -> Vec<u8> {
+    match self {
+      UpdateEncoderVersion::V1 => update.encode_v1(),
+      UpdateEncoderVersion::V2 => update.encode_v2(),
+    }
+  }
+}
+
+impl TryFrom<u8> for UpdateEncoderVersion {
+  type Error = yrs::encoding::read::Error;
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      1 => Ok(UpdateEncoderVersion::V1),
+      2 => Ok(UpdateEncoderVersion::V2),
       _ => Err(yrs::encoding::read::Error::UnexpectedValue),
     }
   }
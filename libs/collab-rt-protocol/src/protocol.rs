@@ -3,11 +3,12 @@ use collab::core::collab::{TransactionExt, TransactionMutExt};
 use collab::core::origin::CollabOrigin;
 use collab::preclude::Collab;
 
-use yrs::updates::decoder::Decode;
 use yrs::updates::encoder::{Encode, Encoder};
 use yrs::{ReadTxn, StateVector, Transact, Update};
 
-use crate::message::{CustomMessage, Message, RTProtocolError, SyncMessage, SyncMeta};
+use crate::message::{
+  CustomMessage, Message, RTProtocolError, SyncMessage, SyncMeta, UpdateEncoderVersion,
+};
 
 // ***************************
 // Client A  Client B  Server
@@ -196,6 +197,23 @@ pub fn handle_message_follow_protocol<P: CollabSyncProtocol>(
   protocol: &P,
   collab: &mut Collab,
   msg: Message,
+) -> Result<Option<Vec<u8>>, RTProtocolError> {
+  handle_message_follow_protocol_with_version(
+    message_origin,
+    protocol,
+    collab,
+    msg,
+    UpdateEncoderVersion::V1,
+  )
+}
+
+/// Same as [handle_message_follow_protocol], but decodes the updates with the given `version`.
+pub fn handle_message_follow_protocol_with_version<P: CollabSyncProtocol>(
+  message_origin: &CollabOrigin,
+  protocol: &P,
+  collab: &mut Collab,
+  msg: Message,
+  version: UpdateEncoderVersion,
 ) -> Result<Option<Vec<u8>>, RTProtocolError> {
   match msg {
     Message::Sync(msg) => match msg {
@@ -208,7 +226,7 @@ pub fn handle_message_follow_protocol<P: CollabSyncProtocol>(
         protocol.handle_sync_step2(
           message_origin,
           collab.get_mut_awareness(),
-          Update::decode_v1(&update)?,
+          version.decode_update(&update)?,
         )?;
         Ok(None)
       },
@@ -216,7 +234,7 @@ pub fn handle_message_follow_protocol<P: CollabSyncProtocol>(
         protocol.handle_update(
           message_origin,
           collab.get_mut_awareness(),
-          Update::decode_v1(&update)?,
+          version.decode_update(&update)?,
         )?;
         Ok(None)
       },
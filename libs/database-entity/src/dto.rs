@@ -284,6 +284,129 @@ pub struct UpdatePublishNamespace {
   pub new_namespace: String,
 }
 
+/// Why a publish namespace was rejected. Returned as the data of the `InvalidRequest` response,
+/// so that clients can tell the user what to fix.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishNamespaceInvalidReason {
+  TooShort,
+  TooLong,
+  InvalidCharacters,
+  Reserved,
+}
+
+impl Display for PublishNamespaceInvalidReason {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      PublishNamespaceInvalidReason::TooShort => {
+        write!(
+          f,
+          "Namespace must be at least {} characters long",
+          PUBLISH_NAMESPACE_MIN_LEN
+        )
+      },
+      PublishNamespaceInvalidReason::TooLong => {
+        write!(
+          f,
+          "Namespace must be at most {} characters long",
+          PUBLISH_NAMESPACE_MAX_LEN
+        )
+      },
+      PublishNamespaceInvalidReason::InvalidCharacters => write!(
+        f,
+        "Namespace must only contain alphanumeric characters and hyphens"
+      ),
+      PublishNamespaceInvalidReason::Reserved => write!(f, "Namespace is reserved"),
+    }
+  }
+}
+
+pub const PUBLISH_NAMESPACE_MIN_LEN: usize = 8;
+pub const PUBLISH_NAMESPACE_MAX_LEN: usize = 50;
+/// Matches the same characters as [char::is_alphanumeric], plus hyphens.
+pub const PUBLISH_NAMESPACE_ALLOWED_CHARS_REGEX: &str = r"^[\p{Alphabetic}\p{N}-]+$";
+/// Namespaces that would clash with the routes of the web app. Compared case-insensitively.
+pub const RESERVED_PUBLISH_NAMESPACES: &[&str] = &[
+  "admin",
+  "api",
+  "app",
+  "appflowy",
+  "auth",
+  "callback",
+  "gotrue",
+  "login",
+  "logout",
+  "publish",
+  "published",
+  "settings",
+  "signup",
+  "template",
+  "templates",
+  "workspace",
+  "ws",
+];
+
+/// The rules a publish namespace must follow. Clients can use [PublishNamespaceRules::validate]
+/// to reject a namespace the same way the server does, before sending it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PublishNamespaceRules {
+  /// Length in characters, not bytes.
+  pub min_len: usize,
+  pub max_len: usize,
+  pub allowed_chars_regex: String,
+  pub reserved: Vec<String>,
+}
+
+impl Default for PublishNamespaceRules {
+  fn default() -> Self {
+    Self {
+      min_len: PUBLISH_NAMESPACE_MIN_LEN,
+      max_len: PUBLISH_NAMESPACE_MAX_LEN,
+      allowed_chars_regex: PUBLISH_NAMESPACE_ALLOWED_CHARS_REGEX.to_string(),
+      reserved: RESERVED_PUBLISH_NAMESPACES
+        .iter()
+        .map(|s| s.to_string())
+        .collect(),
+    }
+  }
+}
+
+impl PublishNamespaceRules {
+  pub fn validate(&self, namespace: &str) -> Result<(), PublishNamespaceInvalidReason> {
+    if self
+      .reserved
+      .iter()
+      .any(|reserved| reserved.eq_ignore_ascii_case(namespace))
+    {
+      return Err(PublishNamespaceInvalidReason::Reserved);
+    }
+    let len = namespace.chars().count();
+    if len < self.min_len {
+      return Err(PublishNamespaceInvalidReason::TooShort);
+    }
+    if len > self.max_len {
+      return Err(PublishNamespaceInvalidReason::TooLong);
+    }
+    if namespace.chars().any(|c| !c.is_alphanumeric() && c != '-') {
+      return Err(PublishNamespaceInvalidReason::InvalidCharacters);
+    }
+    Ok(())
+  }
+}
+
+/// Switches the view backing a published collab while keeping its publish name, so the public
+/// url stays the same.
+#[derive(Serialize, Deserialize)]
+pub struct RepointPublishedView {
+  pub publish_name: String,
+  pub new_view_id: Uuid,
+}
+
+/// Changes the publish name, and therefore the public url, of a published view.
+#[derive(Serialize, Deserialize)]
+pub struct UpdatePublishName {
+  pub new_publish_name: String,
+}
+
 #[derive(Debug, Clone, Validate, Serialize, Deserialize)]
 pub struct QueryCollabMembers {
   #[validate(custom = "validate_not_empty_str")]
@@ -312,6 +435,134 @@ pub struct PublishInfo {
   pub namespace: Option<String>,
   pub publish_name: String,
   pub view_id: Uuid,
+  /// The `title` of the publish metadata. When the request carries an `Accept-Language`
+  /// header and the metadata has `localized_titles`, the best matching title is used.
+  #[serde(default)]
+  pub title: Option<String>,
+  /// The schema version of the publish metadata, see [PublishCollabMetadata::metadata_version].
+  #[serde(default = "default_publish_metadata_version")]
+  pub metadata_version: i32,
+}
+
+/// The schema version of the publish metadata that was published without a version.
+pub const DEFAULT_PUBLISH_METADATA_VERSION: i32 = 1;
+
+fn default_publish_metadata_version() -> i32 {
+  DEFAULT_PUBLISH_METADATA_VERSION
+}
+
+/// A published view of a namespace, built from the stored publish metadata only.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedViewSummary {
+  pub view_id: Uuid,
+  pub publish_name: String,
+  /// The `title` of the publish metadata.
+  pub title: Option<String>,
+  /// The `icon` of the publish metadata.
+  pub icon: Option<String>,
+  pub published_at: DateTime<Utc>,
+}
+
+/// A view published by the user, in any of the workspaces of the user.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedViewOfUser {
+  pub workspace_id: Uuid,
+  /// The publish namespace of the workspace.
+  pub namespace: Option<String>,
+  pub view_id: Uuid,
+  pub publish_name: String,
+  /// The `title` of the publish metadata.
+  pub title: Option<String>,
+  pub published_at: DateTime<Utc>,
+}
+
+/// A database of the workspace that has at least one published view. Linked views of the same
+/// database are grouped under it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedDatabase {
+  pub database_id: String,
+  pub published_views: Vec<PublishInfo>,
+}
+
+/// A publish settings change of a view, recorded in the publish audit log.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+#[repr(i16)]
+pub enum PublishAuditAction {
+  Publish = 0,
+  Unpublish = 1,
+  Repoint = 2,
+  Rename = 3,
+}
+
+impl From<i16> for PublishAuditAction {
+  fn from(value: i16) -> Self {
+    match value {
+      0 => PublishAuditAction::Publish,
+      1 => PublishAuditAction::Unpublish,
+      2 => PublishAuditAction::Repoint,
+      3 => PublishAuditAction::Rename,
+      _ => {
+        error!("Invalid publish audit action: {}", value);
+        PublishAuditAction::Publish
+      },
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishAuditLogEntry {
+  pub view_id: Uuid,
+  pub action: PublishAuditAction,
+  pub actor_uid: i64,
+  pub created_at: DateTime<Utc>,
+}
+
+/// How many times a published view was read. Visitors are told apart by their user id, or by
+/// their ip address for guests. A read is counted when the blob of the view is sent, the
+/// conditional requests answered with `304 Not Modified` are not counted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishedViewStats {
+  pub view_count: i64,
+  pub unique_visitor_count: i64,
+}
+
+/// A version of the published content of a view. The last versions of each published view are
+/// kept so that the view can be restored to one of them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishVersion {
+  pub version: i64,
+  pub published_by: i64,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Result of unpublishing one of the views passed to unpublish. Each view is processed
+/// independently, so a view that can't be unpublished doesn't prevent the others from being
+/// unpublished.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum UnpublishResult {
+  Unpublished,
+  /// The view is not published in the workspace.
+  NotFound,
+  /// The view can't be unpublished, for example because the user is neither the owner of the
+  /// workspace nor the publisher of the view.
+  Error(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnpublishViewResult {
+  pub view_id: Uuid,
+  pub result: UnpublishResult,
+}
+
+/// Open Graph preview of a published view. It is generated when the view is published and
+/// regenerated on every republish.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublishOgMetadata {
+  pub title: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub image: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Hash)]
@@ -496,6 +747,21 @@ pub struct AFUserProfile {
   pub updated_at: i64,
 }
 
+/// The key of the user metadata that holds the url of the avatar of the user.
+pub const USER_METADATA_ICON_URL: &str = "icon_url";
+
+impl AFUserProfile {
+  /// Returns the url of the avatar of the user, which is stored in the user metadata.
+  pub fn icon_url(&self) -> Option<String> {
+    self
+      .metadata
+      .as_ref()?
+      .get(USER_METADATA_ICON_URL)?
+      .as_str()
+      .map(|url| url.to_string())
+  }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AFWorkspace {
   pub workspace_id: Uuid,
@@ -518,6 +784,26 @@ pub struct AFWorkspaceSettings {
 
   #[serde(default)]
   pub ai_model: String,
+
+  #[serde(default)]
+  pub published_view_access: PublishedViewAccess,
+
+  /// When false, nothing can be published in the workspace and its publish namespace can't be
+  /// changed.
+  #[serde(default = "default_publishing_enabled")]
+  pub publishing_enabled: bool,
+
+  /// The least privileged role that can publish and unpublish the views of the workspace.
+  #[serde(default = "default_min_publish_role")]
+  pub min_publish_role: AFRole,
+}
+
+fn default_publishing_enabled() -> bool {
+  true
+}
+
+fn default_min_publish_role() -> AFRole {
+  AFRole::Member
 }
 
 impl Default for AFWorkspaceSettings {
@@ -525,16 +811,37 @@ impl Default for AFWorkspaceSettings {
     Self {
       disable_search_indexing: false,
       ai_model: "".to_string(),
+      published_view_access: PublishedViewAccess::Public,
+      publishing_enabled: true,
+      min_publish_role: AFRole::Member,
     }
   }
 }
 
+/// Who can view the published views of a workspace.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum PublishedViewAccess {
+  /// Anyone with the link, including guests.
+  #[default]
+  Public,
+  /// Only the logged in users.
+  LoggedIn,
+  /// Only the members of the workspace.
+  WorkspaceMember,
+}
+
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct AFWorkspaceSettingsChange {
   #[serde(skip_serializing_if = "Option::is_none")]
   pub disable_search_indexing: Option<bool>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub ai_model: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub published_view_access: Option<PublishedViewAccess>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub publishing_enabled: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub min_publish_role: Option<AFRole>,
 }
 
 impl AFWorkspaceSettingsChange {
@@ -542,6 +849,9 @@ impl AFWorkspaceSettingsChange {
     Self {
       disable_search_indexing: None,
       ai_model: None,
+      published_view_access: None,
+      publishing_enabled: None,
+      min_publish_role: None,
     }
   }
   pub fn disable_search_indexing(mut self, disable_search_indexing: bool) -> Self {
@@ -552,6 +862,18 @@ impl AFWorkspaceSettingsChange {
     self.ai_model = Some(ai_model);
     self
   }
+  pub fn published_view_access(mut self, published_view_access: PublishedViewAccess) -> Self {
+    self.published_view_access = Some(published_view_access);
+    self
+  }
+  pub fn publishing_enabled(mut self, publishing_enabled: bool) -> Self {
+    self.publishing_enabled = Some(publishing_enabled);
+    self
+  }
+  pub fn min_publish_role(mut self, min_publish_role: AFRole) -> Self {
+    self.min_publish_role = Some(min_publish_role);
+    self
+  }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -808,6 +1130,15 @@ pub struct PublishCollabMetadata<Metadata> {
   pub view_id: uuid::Uuid,
   pub publish_name: String,
   pub metadata: Metadata,
+  /// Lowercase hex encoded SHA-256 of the published data. When the view is already published with
+  /// the same hash, publish name and metadata, republishing it is skipped.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub content_hash: Option<String>,
+  /// Version of the schema of `metadata`, so that clients can decode the metadata of views that
+  /// were published with an older shape. The version is stored as
+  /// [DEFAULT_PUBLISH_METADATA_VERSION] when it's not given.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub metadata_version: Option<i32>,
 }
 
 #[derive(Debug)]
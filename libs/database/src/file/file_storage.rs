@@ -126,6 +126,27 @@ where
     Ok(blob)
   }
 
+  /// Puts the blob into the bucket without recording its metadata. The existing content of the
+  /// key is replaced. Used for blobs that are not files of the workspace, such as the blobs of
+  /// published collabs.
+  pub async fn put_blob_without_metadata(
+    &self,
+    key: &impl BlobKey,
+    data: &[u8],
+  ) -> Result<(), AppError> {
+    self.client.pub_blob(key, data).await
+  }
+
+  pub async fn get_blob_by_object_key(&self, object_key: &str) -> Result<Vec<u8>, AppError> {
+    let blob = self.client.get_blob(object_key).await?.to_blob();
+    Ok(blob)
+  }
+
+  pub async fn delete_blob_without_metadata(&self, object_key: &str) -> Result<(), AppError> {
+    self.client.delete_blob(object_key).await?;
+    Ok(())
+  }
+
   pub async fn create_upload(
     &self,
     key: impl BlobKey,
@@ -1,6 +1,7 @@
 use database_entity::dto::{
   AFRole, AFWorkspaceInvitation, AFWorkspaceInvitationStatus, AFWorkspaceSettings,
-  PublishCollabItem, PublishInfo,
+  PublishAuditAction, PublishAuditLogEntry, PublishCollabItem, PublishInfo, PublishVersion,
+  PublishedViewOfUser, PublishedViewStats, PublishedViewSummary, DEFAULT_PUBLISH_METADATA_VERSION,
 };
 use futures_util::stream::BoxStream;
 use sqlx::{types::uuid, Executor, PgPool, Postgres, Transaction};
@@ -800,6 +801,59 @@ pub async fn select_workspace_settings<'a, E: Executor<'a, Database = Postgres>>
     },
   }
 }
+/// Returns the id and the settings of the workspace that owns the publish namespace.
+pub async fn select_workspace_settings_for_publish_namespace<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  publish_namespace: &str,
+) -> Result<(Uuid, Option<AFWorkspaceSettings>), AppError> {
+  let row = sqlx::query!(
+    r#"
+      SELECT workspace_id, settings
+      FROM af_workspace
+      WHERE publish_namespace = $1
+    "#,
+    publish_namespace,
+  )
+  .fetch_one(executor)
+  .await?;
+
+  let settings = match row.settings {
+    None => None,
+    Some(value) => Some(serde_json::from_value::<AFWorkspaceSettings>(value)?),
+  };
+  Ok((row.workspace_id, settings))
+}
+
+/// Returns the workspace that published the view, along with its settings.
+pub async fn select_workspace_settings_for_published_view<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<(Uuid, Option<AFWorkspaceSettings>), AppError> {
+  let row = sqlx::query!(
+    r#"
+      SELECT aw.workspace_id, aw.settings
+      FROM af_workspace aw
+      JOIN af_published_collab apc ON apc.workspace_id = aw.workspace_id
+      WHERE apc.view_id = $1
+    "#,
+    view_id,
+  )
+  .fetch_one(executor)
+  .await?;
+
+  let settings = match row.settings {
+    None => None,
+    Some(value) => Some(serde_json::from_value::<AFWorkspaceSettings>(value)?),
+  };
+  Ok((row.workspace_id, settings))
+}
+
 pub async fn upsert_workspace_settings(
   tx: &mut Transaction<'_, Postgres>,
   workspace_id: &Uuid,
@@ -894,6 +948,9 @@ pub async fn insert_or_replace_publish_collab_metas<'a, E: Executor<'a, Database
   workspace_id: &Uuid,
   publisher_uuid: &Uuid,
   publish_item: &[PublishCollabItem<serde_json::Value, Vec<u8>>],
+  og_metadatas: &[serde_json::Value],
+  blob_s3_keys: &[Option<String>],
+  blob_etags: &[String],
 ) -> Result<(), AppError> {
   let view_ids: Vec<Uuid> = publish_item.iter().map(|item| item.meta.view_id).collect();
   let publish_names: Vec<String> = publish_item
@@ -905,20 +962,52 @@ pub async fn insert_or_replace_publish_collab_metas<'a, E: Executor<'a, Database
     .map(|item| item.meta.metadata.clone())
     .collect();
 
-  let blobs: Vec<Vec<u8>> = publish_item.iter().map(|item| item.data.clone()).collect();
+  // The blob column is left empty when the blob is stored in the S3 bucket
+  let blobs: Vec<Vec<u8>> = publish_item
+    .iter()
+    .zip(blob_s3_keys)
+    .map(|(item, blob_s3_key)| match blob_s3_key {
+      Some(_) => vec![],
+      None => item.data.clone(),
+    })
+    .collect();
+  let content_hashes: Vec<Option<String>> = publish_item
+    .iter()
+    .map(|item| item.meta.content_hash.clone())
+    .collect();
+  let metadata_versions: Vec<i32> = publish_item
+    .iter()
+    .map(|item| {
+      item
+        .meta
+        .metadata_version
+        .unwrap_or(DEFAULT_PUBLISH_METADATA_VERSION)
+    })
+    .collect();
   let res = sqlx::query!(
     r#"
-      INSERT INTO af_published_collab (workspace_id, view_id, publish_name, published_by, metadata, blob)
+      INSERT INTO af_published_collab (workspace_id, view_id, publish_name, published_by, metadata, blob, og_metadata, blob_s3_key, content_hash, metadata_version, blob_etag)
       SELECT * FROM UNNEST(
         (SELECT array_agg((SELECT $1::uuid)) FROM generate_series(1, $7))::uuid[],
         $2::uuid[],
         $3::text[],
         (SELECT array_agg((SELECT uid FROM af_user WHERE uuid = $4)) FROM generate_series(1, $7))::bigint[],
         $5::jsonb[],
-        $6::bytea[]
+        $6::bytea[],
+        $8::jsonb[],
+        $9::text[],
+        $10::text[],
+        $11::int[],
+        $12::text[]
       )
       ON CONFLICT (workspace_id, view_id) DO UPDATE
-      SET metadata = EXCLUDED.metadata
+      SET metadata = EXCLUDED.metadata,
+          blob = EXCLUDED.blob,
+          og_metadata = EXCLUDED.og_metadata,
+          blob_s3_key = EXCLUDED.blob_s3_key,
+          content_hash = EXCLUDED.content_hash,
+          metadata_version = EXCLUDED.metadata_version,
+          blob_etag = EXCLUDED.blob_etag
     "#,
     workspace_id,
     &view_ids,
@@ -927,9 +1016,15 @@ pub async fn insert_or_replace_publish_collab_metas<'a, E: Executor<'a, Database
     &metadatas,
     &blobs,
     publish_item.len() as i32,
+    og_metadatas,
+    blob_s3_keys as &[Option<String>],
+    &content_hashes as &[Option<String>],
+    &metadata_versions,
+    blob_etags,
   )
   .execute(executor)
-  .await?;
+  .await
+  .map_err(publish_name_taken_error)?;
 
   if res.rows_affected() != publish_item.len() as u64 {
     tracing::warn!(
@@ -941,6 +1036,281 @@ pub async fn insert_or_replace_publish_collab_metas<'a, E: Executor<'a, Database
   Ok(())
 }
 
+/// Appends the published content of each view to its version history. The version of a view
+/// starts at 1 and is incremented on every publish. Like the published collab, a version
+/// references the blob by its S3 object key when the blob is stored in the bucket.
+pub async fn insert_publish_collab_versions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  publisher_uuid: &Uuid,
+  publish_items: &[PublishCollabItem<serde_json::Value, Vec<u8>>],
+  blob_s3_keys: &[Option<String>],
+) -> Result<(), AppError> {
+  let view_ids: Vec<Uuid> = publish_items.iter().map(|item| item.meta.view_id).collect();
+  let metadatas: Vec<serde_json::Value> = publish_items
+    .iter()
+    .map(|item| item.meta.metadata.clone())
+    .collect();
+  let blobs: Vec<Vec<u8>> = publish_items
+    .iter()
+    .zip(blob_s3_keys)
+    .map(|(item, blob_s3_key)| match blob_s3_key {
+      Some(_) => vec![],
+      None => item.data.clone(),
+    })
+    .collect();
+  let metadata_versions: Vec<i32> = publish_items
+    .iter()
+    .map(|item| {
+      item
+        .meta
+        .metadata_version
+        .unwrap_or(DEFAULT_PUBLISH_METADATA_VERSION)
+    })
+    .collect();
+  sqlx::query!(
+    r#"
+      INSERT INTO af_published_collab_version (workspace_id, view_id, version, metadata, blob, published_by, metadata_version, blob_s3_key)
+      SELECT
+        $1,
+        item.view_id,
+        COALESCE((
+          SELECT MAX(version)
+          FROM af_published_collab_version apcv
+          WHERE apcv.workspace_id = $1
+            AND apcv.view_id = item.view_id
+        ), 0) + 1,
+        item.metadata,
+        item.blob,
+        (SELECT uid FROM af_user WHERE uuid = $5),
+        item.metadata_version,
+        item.blob_s3_key
+      FROM UNNEST($2::uuid[], $3::jsonb[], $4::bytea[], $6::int[], $7::text[])
+        AS item(view_id, metadata, blob, metadata_version, blob_s3_key)
+    "#,
+    workspace_id,
+    &view_ids,
+    &metadatas,
+    &blobs,
+    publisher_uuid,
+    &metadata_versions,
+    blob_s3_keys as &[Option<String>],
+  )
+  .execute(executor)
+  .await
+  .map_err(publish_version_conflict_error)?;
+  Ok(())
+}
+
+/// The version of a view is computed from the latest one, so two concurrent publishes of the
+/// same view insert the same version.
+fn publish_version_conflict_error(err: sqlx::Error) -> AppError {
+  match &err {
+    sqlx::Error::Database(db_err)
+      if db_err.constraint() == Some("af_published_collab_version_pkey") =>
+    {
+      AppError::PublishVersionConflict(format!("the view was published concurrently: {}", db_err))
+    },
+    _ => AppError::from(err),
+  }
+}
+
+/// Deletes the versions of the views that are older than the last `keep` versions. Returns the S3
+/// object keys of the blobs of the deleted versions.
+pub async fn delete_old_publish_collab_versions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  keep: i64,
+) -> Result<Vec<String>, AppError> {
+  let keys = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_published_collab_version apcv
+      WHERE apcv.workspace_id = $1
+        AND apcv.view_id = ANY($2)
+        AND apcv.version <= (
+          SELECT MAX(version)
+          FROM af_published_collab_version latest
+          WHERE latest.workspace_id = apcv.workspace_id
+            AND latest.view_id = apcv.view_id
+        ) - $3
+      RETURNING apcv.blob_s3_key
+    "#,
+    workspace_id,
+    view_ids,
+    keep,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(keys.into_iter().flatten().collect())
+}
+
+/// Deletes all the versions of the views. Returns the S3 object keys of the blobs of the deleted
+/// versions.
+pub async fn delete_publish_collab_versions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<String>, AppError> {
+  let keys = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_published_collab_version
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+      RETURNING blob_s3_key
+    "#,
+    workspace_id,
+    view_ids,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(keys.into_iter().flatten().collect())
+}
+
+/// Deletes the versions of all the published views of the workspace. Returns the S3 object keys
+/// of the blobs of the deleted versions.
+pub async fn delete_all_publish_collab_versions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<String>, AppError> {
+  let keys = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_published_collab_version
+      WHERE workspace_id = $1
+      RETURNING blob_s3_key
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(keys.into_iter().flatten().collect())
+}
+
+/// Returns the S3 object keys among `blob_s3_keys` that are referenced neither by a published
+/// collab nor by a version of the workspace, so that their blobs can be deleted.
+pub async fn select_unreferenced_published_blob_s3_keys<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  blob_s3_keys: &[String],
+) -> Result<Vec<String>, AppError> {
+  let keys = sqlx::query_scalar!(
+    r#"
+      SELECT released.key AS "key!"
+      FROM UNNEST($2::text[]) AS released(key)
+      WHERE NOT EXISTS (
+          SELECT 1 FROM af_published_collab
+          WHERE workspace_id = $1 AND blob_s3_key = released.key
+        )
+        AND NOT EXISTS (
+          SELECT 1 FROM af_published_collab_version
+          WHERE workspace_id = $1 AND blob_s3_key = released.key
+        )
+    "#,
+    workspace_id,
+    blob_s3_keys,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(keys)
+}
+
+/// Returns the versions of the published view, newest first.
+pub async fn select_publish_collab_versions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<PublishVersion>, AppError> {
+  let versions = sqlx::query_as!(
+    PublishVersion,
+    r#"
+      SELECT version, published_by, created_at
+      FROM af_published_collab_version
+      WHERE workspace_id = $1
+        AND view_id = $2
+      ORDER BY version DESC
+    "#,
+    workspace_id,
+    view_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(versions)
+}
+
+/// Returns the latest version of the published view. The published view is locked until the end
+/// of the transaction, so that the view can't be published concurrently in the meantime.
+pub async fn select_latest_publish_collab_version_for_update(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Option<i64>, AppError> {
+  sqlx::query!(
+    r#"
+      SELECT view_id
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND view_id = $2
+      FOR UPDATE
+    "#,
+    workspace_id,
+    view_id,
+  )
+  .fetch_optional(txn.deref_mut())
+  .await?;
+
+  let version = sqlx::query_scalar!(
+    r#"
+      SELECT MAX(version)
+      FROM af_published_collab_version
+      WHERE workspace_id = $1
+        AND view_id = $2
+    "#,
+    workspace_id,
+    view_id,
+  )
+  .fetch_one(txn.deref_mut())
+  .await?;
+  Ok(version)
+}
+
+/// Returns the current publish name of the view, and the metadata, the blob, the S3 object key
+/// of the blob and the metadata schema version of the version. When the object key is not None,
+/// the blob is stored in the S3 bucket and the blob column is empty.
+pub async fn select_publish_collab_version<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  version: i64,
+) -> Result<(String, serde_json::Value, Vec<u8>, Option<String>, i32), AppError> {
+  let res = sqlx::query!(
+    r#"
+      SELECT apc.publish_name, apcv.metadata, apcv.blob, apcv.blob_s3_key, apcv.metadata_version
+      FROM af_published_collab_version apcv
+      JOIN af_published_collab apc
+        ON apc.workspace_id = apcv.workspace_id
+        AND apc.view_id = apcv.view_id
+      WHERE apcv.workspace_id = $1
+        AND apcv.view_id = $2
+        AND apcv.version = $3
+    "#,
+    workspace_id,
+    view_id,
+    version,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok((
+    res.publish_name,
+    res.metadata,
+    res.blob,
+    res.blob_s3_key,
+    res.metadata_version,
+  ))
+}
+
 #[inline]
 pub async fn select_publish_collab_meta<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
@@ -963,70 +1333,695 @@ pub async fn select_publish_collab_meta<'a, E: Executor<'a, Database = Postgres>
   Ok(metadata)
 }
 
+/// Returns the view id of the published collab and its stored Open Graph metadata, if any.
 #[inline]
-pub async fn delete_published_collabs<'a, E: Executor<'a, Database = Postgres>>(
+pub async fn select_published_og_metadata<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
-  workspace_id: &Uuid,
-  view_ids: &[Uuid],
-) -> Result<(), AppError> {
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<(Uuid, Option<serde_json::Value>), AppError> {
   let res = sqlx::query!(
     r#"
-      DELETE FROM af_published_collab
-      WHERE workspace_id = $1
-        AND view_id = ANY($2)
+    SELECT view_id, og_metadata
+    FROM af_published_collab
+    WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
+      AND publish_name = $2
     "#,
-    workspace_id,
-    view_ids,
+    publish_namespace,
+    publish_name,
   )
-  .execute(executor)
+  .fetch_one(executor)
   .await?;
-
-  if res.rows_affected() != view_ids.len() as u64 {
-    tracing::error!(
-      "Failed to delete published collabs, workspace_id: {}, view_ids: {:?}, rows_affected: {}",
-      workspace_id,
-      view_ids,
-      res.rows_affected()
-    );
-  }
-
-  Ok(())
+  Ok((res.view_id, res.og_metadata))
 }
 
 #[inline]
-pub async fn select_published_collab_blob<'a, E: Executor<'a, Database = Postgres>>(
+pub async fn select_published_view_id_for_publish_name<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
-  publish_namespace: &str,
+  workspace_id: &Uuid,
   publish_name: &str,
-) -> Result<Vec<u8>, AppError> {
-  let res = sqlx::query_scalar!(
+) -> Result<Uuid, AppError> {
+  let view_id = sqlx::query_scalar!(
     r#"
-      SELECT blob
+      SELECT view_id
       FROM af_published_collab
-      WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
-      AND publish_name = $2
+      WHERE workspace_id = $1
+        AND publish_name = $2
     "#,
-    publish_namespace,
+    workspace_id,
     publish_name,
   )
   .fetch_one(executor)
   .await?;
+  Ok(view_id)
+}
 
-  Ok(res)
+#[inline]
+pub async fn select_view_is_published<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS(
+        SELECT 1
+        FROM af_published_collab
+        WHERE workspace_id = $1
+          AND view_id = $2
+      )
+    "#,
+    workspace_id,
+    view_id,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists.unwrap_or(false))
 }
 
-pub async fn select_published_collab_info<'a, E: Executor<'a, Database = Postgres>>(
+/// Returns the partition key (the collab type) of the collab in the workspace, or `None` if
+/// the collab does not exist.
+#[inline]
+pub async fn select_collab_partition_key_in_workspace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oid: &str,
+) -> Result<Option<i32>, AppError> {
+  let partition_key = sqlx::query_scalar!(
+    r#"
+      SELECT partition_key
+      FROM af_collab
+      WHERE workspace_id = $1
+        AND oid = $2
+        AND deleted_at IS NULL
+    "#,
+    workspace_id,
+    oid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(partition_key)
+}
+
+#[inline]
+/// Maps the violation of the unique publish name constraint to [AppError::PublishNameAlreadyTaken].
+fn publish_name_taken_error(err: sqlx::Error) -> AppError {
+  match &err {
+    sqlx::Error::Database(db_err)
+      if db_err.constraint() == Some("af_published_collab_publish_name_key") =>
+    {
+      AppError::PublishNameAlreadyTaken(format!(
+        "publish name is already used by another view: {}",
+        db_err
+      ))
+    },
+    _ => AppError::from(err),
+  }
+}
+
+pub async fn update_published_collab_publish_name<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
+  workspace_id: &Uuid,
   view_id: &Uuid,
-) -> Result<PublishInfo, AppError> {
-  let res = sqlx::query_as!(
-    PublishInfo,
+  new_publish_name: &str,
+) -> Result<(), AppError> {
+  let res = sqlx::query!(
     r#"
-      SELECT
-        (SELECT publish_namespace FROM af_workspace aw WHERE aw.workspace_id = apc.workspace_id) AS namespace,
-        publish_name,
-        view_id
-      FROM af_published_collab apc
+      UPDATE af_published_collab
+      SET publish_name = $3
+      WHERE workspace_id = $1
+        AND view_id = $2
+    "#,
+    workspace_id,
+    view_id,
+    new_publish_name,
+  )
+  .execute(executor)
+  .await
+  .map_err(publish_name_taken_error)?;
+
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "View {} is not published",
+      view_id
+    )));
+  }
+
+  Ok(())
+}
+
+pub async fn update_published_collab_view_id<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  publish_name: &str,
+  new_view_id: &Uuid,
+) -> Result<(), AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_published_collab
+      SET view_id = $3
+      WHERE workspace_id = $1
+        AND publish_name = $2
+    "#,
+    workspace_id,
+    publish_name,
+    new_view_id,
+  )
+  .execute(executor)
+  .await?;
+
+  if res.rows_affected() != 1 {
+    tracing::error!(
+      "Failed to repoint published collab, workspace_id: {}, publish_name: {}, rows_affected: {}",
+      workspace_id,
+      publish_name,
+      res.rows_affected()
+    );
+  }
+
+  Ok(())
+}
+
+#[inline]
+pub async fn insert_publish_audit_logs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  action: PublishAuditAction,
+  actor_uuid: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_published_collab_audit_log (workspace_id, view_id, action, actor_uid)
+      SELECT $1, view_id, $3, (SELECT uid FROM af_user WHERE uuid = $4)
+      FROM UNNEST($2::uuid[]) AS view_id
+    "#,
+    workspace_id,
+    view_ids,
+    action as i16,
+    actor_uuid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+#[inline]
+pub async fn select_publish_audit_log<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<PublishAuditLogEntry>, AppError> {
+  let entries = sqlx::query_as!(
+    PublishAuditLogEntry,
+    r#"
+      SELECT view_id, action, actor_uid, created_at
+      FROM af_published_collab_audit_log
+      WHERE workspace_id = $1
+        AND view_id = $2
+      ORDER BY id ASC
+    "#,
+    workspace_id,
+    view_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(entries)
+}
+
+/// Adds a read to the stats of the published view. When `visitor_key` is given, the visitor is
+/// also recorded, so that the distinct visitors can be counted. The reads of the owner of the
+/// workspace or of the publisher of the view, identified by `uid`, are also counted separately.
+pub async fn insert_published_view_visit<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+  visitor_key: Option<&str>,
+  uid: Option<i64>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      WITH published_view AS (
+        SELECT
+          apc.workspace_id,
+          apc.view_id,
+          COALESCE($4 IN (apc.published_by, aw.owner_uid), FALSE) AS is_owner
+        FROM af_published_collab apc
+        JOIN af_workspace aw ON apc.workspace_id = aw.workspace_id
+        WHERE aw.publish_namespace = $1
+          AND apc.publish_name = $2
+      ), visitor AS (
+        INSERT INTO af_published_view_visitor (workspace_id, view_id, visitor_key, is_owner)
+        SELECT workspace_id, view_id, md5($3::text), is_owner
+        FROM published_view
+        WHERE $3::text IS NOT NULL
+        ON CONFLICT DO NOTHING
+      )
+      INSERT INTO af_published_view_stats (workspace_id, view_id, view_count, owner_view_count)
+      SELECT workspace_id, view_id, 1, CASE WHEN is_owner THEN 1 ELSE 0 END
+      FROM published_view
+      ON CONFLICT (workspace_id, view_id) DO UPDATE
+      SET view_count = af_published_view_stats.view_count + 1,
+          owner_view_count = af_published_view_stats.owner_view_count + EXCLUDED.owner_view_count
+    "#,
+    publish_namespace,
+    publish_name,
+    visitor_key,
+    uid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_published_view_stats<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  exclude_owner: bool,
+) -> Result<PublishedViewStats, AppError> {
+  let res = sqlx::query_as!(
+    PublishedViewStats,
+    r#"
+      SELECT
+        COALESCE((
+          SELECT CASE WHEN $3 THEN view_count - owner_view_count ELSE view_count END
+          FROM af_published_view_stats
+          WHERE workspace_id = $1 AND view_id = $2
+        ), 0) AS "view_count!",
+        (
+          SELECT COUNT(*)
+          FROM af_published_view_visitor
+          WHERE workspace_id = $1 AND view_id = $2 AND NOT ($3 AND is_owner)
+        ) AS "unique_visitor_count!"
+    "#,
+    workspace_id,
+    view_id,
+    exclude_owner,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(res)
+}
+
+/// Returns the view ids of the items that are published with the same content hash, publish name,
+/// metadata and metadata version. Republishing these items doesn't change anything.
+pub async fn select_unchanged_published_view_ids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  publish_items: &[PublishCollabItem<serde_json::Value, Vec<u8>>],
+) -> Result<Vec<Uuid>, AppError> {
+  let view_ids: Vec<Uuid> = publish_items.iter().map(|item| item.meta.view_id).collect();
+  let res = sqlx::query!(
+    r#"
+      SELECT view_id, publish_name, metadata, content_hash, metadata_version
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+        AND content_hash IS NOT NULL
+    "#,
+    workspace_id,
+    &view_ids,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  let unchanged_view_ids = res
+    .into_iter()
+    .filter(|row| {
+      publish_items.iter().any(|item| {
+        item.meta.view_id == row.view_id
+          && item.meta.content_hash == row.content_hash
+          && item.meta.publish_name == row.publish_name
+          && item.meta.metadata == row.metadata
+          && item
+            .meta
+            .metadata_version
+            .unwrap_or(DEFAULT_PUBLISH_METADATA_VERSION)
+            == row.metadata_version
+      })
+    })
+    .map(|row| row.view_id)
+    .collect();
+  Ok(unchanged_view_ids)
+}
+
+/// Returns the view ids published by the request sent with the idempotency key, if the key was
+/// used in the last `expire_hours` hours.
+pub async fn select_publish_idempotency_result<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  idempotency_key: &str,
+  expire_hours: i32,
+) -> Result<Option<Vec<Uuid>>, AppError> {
+  let res = sqlx::query_scalar!(
+    r#"
+      SELECT published_view_ids
+      FROM af_publish_idempotency_key
+      WHERE workspace_id = $1
+        AND idempotency_key = $2
+        AND created_at > NOW() - make_interval(hours => $3)
+    "#,
+    workspace_id,
+    idempotency_key,
+    expire_hours,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(res)
+}
+
+/// Records the view ids published by the request sent with the idempotency key. Returns false if
+/// the key is already recorded. When a concurrent transaction records the same key, the insert
+/// waits for it to finish, so that the requests with the same key can't both publish.
+pub async fn insert_publish_idempotency_result(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  idempotency_key: &str,
+  published_view_ids: &[Uuid],
+  expire_hours: i32,
+) -> Result<bool, AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_publish_idempotency_key
+      WHERE workspace_id = $1
+        AND created_at <= NOW() - make_interval(hours => $2)
+    "#,
+    workspace_id,
+    expire_hours,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_publish_idempotency_key (workspace_id, idempotency_key, published_view_ids)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, idempotency_key) DO NOTHING
+    "#,
+    workspace_id,
+    idempotency_key,
+    published_view_ids,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns the published views among `view_ids`, along with whether each of them was published
+/// by the user. The views that are not published are not returned.
+pub async fn select_published_views_publisher<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<(Uuid, bool)>, AppError> {
+  let res = sqlx::query!(
+    r#"
+      SELECT
+        view_id,
+        published_by = (SELECT uid FROM af_user WHERE uuid = $3) AS "is_publisher!"
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+    "#,
+    workspace_id,
+    view_ids,
+    user_uuid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    res
+      .into_iter()
+      .map(|row| (row.view_id, row.is_publisher))
+      .collect(),
+  )
+}
+
+/// Deletes the published collabs. Returns the view id and the S3 object key of the blob of each
+/// deleted collab. The views that are not published are not returned.
+#[inline]
+pub async fn delete_published_collabs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<(Uuid, Option<String>)>, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_published_collab
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+      RETURNING view_id, blob_s3_key
+    "#,
+    workspace_id,
+    view_ids,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    res
+      .into_iter()
+      .map(|row| (row.view_id, row.blob_s3_key))
+      .collect(),
+  )
+}
+
+/// Deletes all the published collabs of the workspace. Returns the view id and the S3 object key
+/// of the blob of each deleted collab.
+pub async fn delete_all_published_collabs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(Uuid, Option<String>)>, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_published_collab
+      WHERE workspace_id = $1
+      RETURNING view_id, blob_s3_key
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    res
+      .into_iter()
+      .map(|row| (row.view_id, row.blob_s3_key))
+      .collect(),
+  )
+}
+
+/// Returns the blob column and the S3 object key of the published collab. When the object key
+/// is not None, the blob is stored in the S3 bucket and the blob column is empty.
+#[inline]
+pub async fn select_published_collab_blob<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<(Vec<u8>, Option<String>), AppError> {
+  let res = sqlx::query!(
+    r#"
+      SELECT blob, blob_s3_key
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
+      AND publish_name = $2
+    "#,
+    publish_namespace,
+    publish_name,
+  )
+  .fetch_one(executor)
+  .await?;
+
+  Ok((res.blob, res.blob_s3_key))
+}
+
+/// Returns the ETag of the blob of the published view. It is computed when the view is published,
+/// so that answering a conditional request doesn't read the blob.
+pub async fn select_published_collab_blob_etag<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<String, AppError> {
+  let etag = sqlx::query_scalar!(
+    r#"
+      SELECT blob_etag
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace WHERE publish_namespace = $1)
+      AND publish_name = $2
+    "#,
+    publish_namespace,
+    publish_name,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(etag)
+}
+
+/// Returns the S3 object keys of the blobs of the given published views. Views whose blob is
+/// stored in Postgres are skipped. The rows of the views are locked until the end of the
+/// transaction, so that a concurrent publish can't replace the blobs in the meantime.
+#[inline]
+pub async fn select_published_blob_s3_keys_for_update(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<String>, AppError> {
+  let keys = sqlx::query_scalar!(
+    r#"
+      SELECT blob_s3_key
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+      FOR UPDATE
+    "#,
+    workspace_id,
+    view_ids,
+  )
+  .fetch_all(txn.deref_mut())
+  .await?;
+  Ok(keys.into_iter().flatten().collect())
+}
+
+/// Returns a publish name among `publish_names` that is already used by a published view of the
+/// workspace which is not in `view_ids`, along with that view.
+pub async fn select_published_view_using_publish_name<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  publish_names: &[String],
+  view_ids: &[Uuid],
+) -> Result<Option<(String, Uuid)>, AppError> {
+  let res = sqlx::query!(
+    r#"
+      SELECT publish_name, view_id
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND publish_name = ANY($2)
+        AND NOT view_id = ANY($3)
+      LIMIT 1
+    "#,
+    workspace_id,
+    publish_names,
+    view_ids,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(res.map(|row| (row.publish_name, row.view_id)))
+}
+
+pub async fn select_published_collab_info<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<PublishInfo, AppError> {
+  let res = sqlx::query_as!(
+    PublishInfo,
+    r#"
+      SELECT
+        (SELECT publish_namespace FROM af_workspace aw WHERE aw.workspace_id = apc.workspace_id) AS namespace,
+        publish_name,
+        view_id,
+        metadata->>'title' AS title,
+        metadata_version
+      FROM af_published_collab apc
+      WHERE view_id = $1
+    "#,
+    view_id,
+  )
+  .fetch_one(executor)
+  .await?;
+
+  Ok(res)
+}
+
+pub async fn select_workspace_published_collab_infos<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<PublishInfo>, AppError> {
+  let res = sqlx::query_as!(
+    PublishInfo,
+    r#"
+      SELECT
+        (SELECT publish_namespace FROM af_workspace aw WHERE aw.workspace_id = apc.workspace_id) AS namespace,
+        publish_name,
+        view_id,
+        metadata->>'title' AS title,
+        metadata_version
+      FROM af_published_collab apc
+      WHERE workspace_id = $1
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Returns the published views of the namespace, most recently published first. The blobs are
+/// not loaded.
+pub async fn select_namespace_published_view_summaries<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+) -> Result<Vec<PublishedViewSummary>, AppError> {
+  let res = sqlx::query_as!(
+    PublishedViewSummary,
+    r#"
+      SELECT
+        apc.view_id,
+        apc.publish_name,
+        apc.metadata->>'title' AS title,
+        apc.metadata->>'icon' AS icon,
+        apc.created_at AS "published_at!"
+      FROM af_published_collab apc
+      JOIN af_workspace aw ON aw.workspace_id = apc.workspace_id
+      WHERE aw.publish_namespace = $1
+      ORDER BY apc.created_at DESC
+    "#,
+    publish_namespace,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Returns the views published by the user across all workspaces, most recently published first.
+/// The blobs are not loaded.
+pub async fn select_published_views_of_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  user_uuid: &Uuid,
+) -> Result<Vec<PublishedViewOfUser>, AppError> {
+  let res = sqlx::query_as!(
+    PublishedViewOfUser,
+    r#"
+      SELECT
+        apc.workspace_id,
+        aw.publish_namespace AS namespace,
+        apc.view_id,
+        apc.publish_name,
+        apc.metadata->>'title' AS title,
+        apc.created_at AS "published_at!"
+      FROM af_published_collab apc
+      JOIN af_workspace aw ON aw.workspace_id = apc.workspace_id
+      WHERE apc.published_by = (SELECT uid FROM af_user WHERE uuid = $1)
+      ORDER BY apc.created_at DESC
+    "#,
+    user_uuid,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+#[inline]
+pub async fn select_published_metadata_for_view_id<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<serde_json::Value, AppError> {
+  let res = sqlx::query_scalar!(
+    r#"
+      SELECT metadata
+      FROM af_published_collab
       WHERE view_id = $1
     "#,
     view_id,
@@ -1,7 +1,7 @@
 use super::grant::Grant;
 use crate::params::{
   AdminDeleteUserParams, AdminUserParams, CreateSSOProviderParams, GenerateLinkParams,
-  GenerateLinkResponse, InviteUserParams, MagicLinkParams,
+  GenerateLinkResponse, InviteUserParams, MagicLinkParams, ResendParams,
 };
 use anyhow::Context;
 use gotrue_entity::dto::{
@@ -11,7 +11,7 @@ use gotrue_entity::dto::{
 use gotrue_entity::error::{GoTrueError, GoTrueErrorSerde, GotrueClientError};
 use gotrue_entity::sso::{SSOProvider, SSOProviders};
 use infra::reqwest::{check_response, from_body, from_response};
-use reqwest::{Method, RequestBuilder};
+use reqwest::{Method, RequestBuilder, StatusCode};
 use tracing::event;
 
 #[derive(Clone)]
@@ -86,6 +86,11 @@ impl Client {
       Ok(token)
     } else if resp.status().is_client_error() {
       Err(from_body::<GotrueClientError>(resp).await?.into())
+    } else if is_service_unavailable(resp.status()) {
+      Err(GoTrueError::ServiceUnavailable(format!(
+        "gotrue responded with {}",
+        resp.status()
+      )))
     } else {
       Err(anyhow::anyhow!("unexpected response status: {}", resp.status()).into())
     }
@@ -249,6 +254,19 @@ impl Client {
     check_gotrue_result(resp).await
   }
 
+  /// Sends the confirmation email again. gotrue limits how often an email can be sent to the
+  /// same address.
+  pub async fn resend(&self, resend_params: &ResendParams) -> Result<(), GoTrueError> {
+    let url = format!("{}/resend", self.base_url);
+    let resp = self
+      .client
+      .request(Method::POST, &url)
+      .json(resend_params)
+      .send()
+      .await?;
+    check_gotrue_result(resp).await
+  }
+
   pub async fn admin_list_sso_providers(
     &self,
     access_token: &str,
@@ -333,6 +351,11 @@ where
   if resp.status().is_success() {
     let t: T = from_body(resp).await?;
     Ok(t)
+  } else if is_service_unavailable(resp.status()) {
+    Err(GoTrueError::ServiceUnavailable(format!(
+      "gotrue responded with {}",
+      resp.status()
+    )))
   } else {
     let err: GoTrueErrorSerde = from_body(resp).await?;
     Err(GoTrueError::Internal(err))
@@ -342,8 +365,21 @@ where
 async fn check_gotrue_result(resp: reqwest::Response) -> Result<(), GoTrueError> {
   if resp.status().is_success() {
     Ok(())
+  } else if is_service_unavailable(resp.status()) {
+    Err(GoTrueError::ServiceUnavailable(format!(
+      "gotrue responded with {}",
+      resp.status()
+    )))
   } else {
     let err: GoTrueErrorSerde = from_body(resp).await?;
     Err(GoTrueError::Internal(err))
   }
 }
+
+/// The reverse proxy in front of gotrue responds with these status codes when gotrue is down.
+fn is_service_unavailable(status: StatusCode) -> bool {
+  matches!(
+    status,
+    StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+  )
+}
@@ -75,6 +75,20 @@ pub enum GenerateLinkType {
   Email,
 }
 
+#[derive(Serialize)]
+pub struct ResendParams {
+  #[serde(rename = "type")]
+  pub type_: ResendType,
+  pub email: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResendType {
+  Signup,
+  EmailChange,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GenerateLinkResponse {
   // putting User here as Rust does not support struct field extension
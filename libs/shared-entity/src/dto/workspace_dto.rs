@@ -48,6 +48,20 @@ pub struct WorkspaceInviteQuery {
   pub status: Option<AFWorkspaceInvitationStatus>,
 }
 
+#[derive(Deserialize)]
+pub struct RestorePublishVersionQuery {
+  /// The latest version of the view known by the client. The restore fails with
+  /// `ErrorCode::PublishVersionConflict` when the view was published again since.
+  pub latest_version: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PublishedViewStatsQuery {
+  /// Don't count the reads of the owner of the workspace and of the publisher of the view.
+  #[serde(default)]
+  pub exclude_owner: bool,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct WorkspaceMemberChangeset {
   pub email: String,
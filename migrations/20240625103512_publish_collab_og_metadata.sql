@@ -0,0 +1,2 @@
+-- Open Graph preview metadata, generated at publish time
+ALTER TABLE af_published_collab ADD COLUMN og_metadata JSONB;
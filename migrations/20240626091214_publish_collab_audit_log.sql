@@ -0,0 +1,12 @@
+-- append-only history of the publish settings changes of a view
+CREATE TABLE IF NOT EXISTS af_published_collab_audit_log (
+    id           BIGSERIAL PRIMARY KEY,
+    workspace_id UUID     NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id      UUID     NOT NULL,
+    action       SMALLINT NOT NULL,
+    actor_uid    BIGINT   NOT NULL,
+    created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS af_published_collab_audit_log_view_idx
+    ON af_published_collab_audit_log(workspace_id, view_id);
@@ -0,0 +1,2 @@
+-- object key of the blob when it is stored in the S3 bucket instead of the blob column
+ALTER TABLE af_published_collab ADD COLUMN blob_s3_key TEXT;
@@ -0,0 +1,18 @@
+-- a publish name can only be used by one view of a workspace. The latest published view keeps the
+-- publish name, the other views are renamed with their view id so that they stay published.
+-- The new name is at most 50 characters long: 17 characters of the old name, a hyphen and the 32
+-- hex digits of the view id. `updated_at` and `created_at` are nullable, so the views are ordered
+-- with the epoch in place of the missing timestamps, and the view id breaks the ties.
+UPDATE af_published_collab a
+SET publish_name = LEFT(a.publish_name, 17) || '-' || REPLACE(a.view_id::text, '-', '')
+WHERE EXISTS (
+    SELECT 1
+    FROM af_published_collab b
+    WHERE b.workspace_id = a.workspace_id
+      AND b.publish_name = a.publish_name
+      AND (COALESCE(a.updated_at, a.created_at, 'epoch'), a.view_id)
+        < (COALESCE(b.updated_at, b.created_at, 'epoch'), b.view_id)
+);
+
+ALTER TABLE af_published_collab
+    ADD CONSTRAINT af_published_collab_publish_name_key UNIQUE (workspace_id, publish_name);
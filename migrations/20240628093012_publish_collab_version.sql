@@ -0,0 +1,14 @@
+-- bounded history of the published content of a view, used to restore a previous version.
+-- The history is removed along with the published view when it is unpublished.
+CREATE TABLE IF NOT EXISTS af_published_collab_version (
+    workspace_id UUID   NOT NULL,
+    view_id      UUID   NOT NULL,
+    version      BIGINT NOT NULL,
+    metadata     JSONB  NOT NULL,
+    blob         BYTEA  NOT NULL,
+    published_by BIGINT NOT NULL,
+    created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, view_id, version),
+    FOREIGN KEY (workspace_id, view_id) REFERENCES af_published_collab(workspace_id, view_id)
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- hash of the published data provided by the client, used to skip republishing unchanged collabs
+ALTER TABLE af_published_collab ADD COLUMN content_hash TEXT;
@@ -0,0 +1,23 @@
+-- number of times the blob of a published view was read. The views of the owner of the workspace
+-- or the publisher of the view are also counted separately, so that they can be excluded.
+CREATE TABLE IF NOT EXISTS af_published_view_stats (
+    workspace_id     UUID   NOT NULL,
+    view_id          UUID   NOT NULL,
+    view_count       BIGINT NOT NULL DEFAULT 0,
+    owner_view_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, view_id),
+    FOREIGN KEY (workspace_id, view_id) REFERENCES af_published_collab(workspace_id, view_id)
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+-- distinct visitors of a published view. The visitor key is the md5 of the user id, or of the ip
+-- address for guests.
+CREATE TABLE IF NOT EXISTS af_published_view_visitor (
+    workspace_id UUID    NOT NULL,
+    view_id      UUID    NOT NULL,
+    visitor_key  TEXT    NOT NULL,
+    is_owner     BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (workspace_id, view_id, visitor_key),
+    FOREIGN KEY (workspace_id, view_id) REFERENCES af_published_collab(workspace_id, view_id)
+        ON UPDATE CASCADE ON DELETE CASCADE
+);
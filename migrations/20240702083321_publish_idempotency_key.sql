@@ -0,0 +1,9 @@
+-- result of the publish requests sent with an idempotency key, so that a retried request returns
+-- the original result instead of publishing the collabs again
+CREATE TABLE IF NOT EXISTS af_publish_idempotency_key (
+    workspace_id       UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    idempotency_key    TEXT        NOT NULL,
+    published_view_ids UUID[]      NOT NULL,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (workspace_id, idempotency_key)
+);
@@ -0,0 +1,6 @@
+-- version of the schema of the publish metadata, so that clients can decode the metadata that was
+-- published with an older shape. The existing records are version 1.
+ALTER TABLE af_published_collab
+    ADD COLUMN IF NOT EXISTS metadata_version INT NOT NULL DEFAULT 1;
+ALTER TABLE af_published_collab_version
+    ADD COLUMN IF NOT EXISTS metadata_version INT NOT NULL DEFAULT 1;
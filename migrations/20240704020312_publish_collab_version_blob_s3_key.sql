@@ -0,0 +1,4 @@
+-- the blobs of the versions that are stored in the S3 bucket are referenced by their object key,
+-- like in af_published_collab, and the blob column is left empty
+ALTER TABLE af_published_collab_version
+    ADD COLUMN IF NOT EXISTS blob_s3_key TEXT;
@@ -0,0 +1,5 @@
+-- ETag of the published blob, computed when the view is published so that answering a
+-- conditional request doesn't read the blob
+ALTER TABLE af_published_collab ADD COLUMN blob_etag TEXT;
+UPDATE af_published_collab SET blob_etag = md5(COALESCE(blob_s3_key, '') || md5(blob));
+ALTER TABLE af_published_collab ALTER COLUMN blob_etag SET NOT NULL;
@@ -155,6 +155,23 @@ where
     }
   }
 
+  /// Asks all the clients that are currently editing a collab of the given workspace to discard
+  /// their sync state and perform a fresh init sync.
+  pub async fn force_resync_workspace(&self, workspace_id: &str) -> Result<(), AppError> {
+    self
+      .rt_cmd_sender
+      .send(CollaborationCommand::ForceResyncWorkspace {
+        workspace_id: workspace_id.to_string(),
+      })
+      .await
+      .map_err(|err| {
+        AppError::Internal(anyhow::anyhow!(
+          "Failed to send force resync command to realtime server: {}",
+          err
+        ))
+      })
+  }
+
   async fn queue_insert_collab(
     &self,
     workspace_id: &str,
@@ -345,6 +362,18 @@ where
       });
     }
     self.cache.delete_collab(object_id).await?;
+    if let Err(err) = self
+      .rt_cmd_sender
+      .send(CollaborationCommand::CollabDeleted {
+        object_id: object_id.to_string(),
+      })
+      .await
+    {
+      error!(
+        "Failed to send collab deleted command to realtime server: {}",
+        err
+      );
+    }
     Ok(())
   }
 
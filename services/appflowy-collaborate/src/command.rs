@@ -13,6 +13,11 @@ pub enum CollaborationCommand {
     object_id: String,
     ret: EncodeCollabSender,
   },
+  /// Ask all the clients that are editing a collab of the given workspace to perform a fresh
+  /// init sync.
+  ForceResyncWorkspace { workspace_id: String },
+  /// Tell the clients that are editing the collab that it has been deleted.
+  CollabDeleted { object_id: String },
 }
 
 pub(crate) fn spawn_collaboration_command(
@@ -41,6 +46,35 @@ pub(crate) fn spawn_collaboration_command(
             },
           }
         },
+        CollaborationCommand::ForceResyncWorkspace { workspace_id } => {
+          let senders = group_sender_by_object_id
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect::<Vec<_>>();
+          for (object_id, sender) in senders {
+            if let Err(err) = sender
+              .send(GroupCommand::ForceResync {
+                object_id,
+                workspace_id: workspace_id.clone(),
+              })
+              .await
+            {
+              error!("Send group command error: {}", err);
+            }
+          }
+        },
+        CollaborationCommand::CollabDeleted { object_id } => {
+          if let Some(sender) = group_sender_by_object_id.get(&object_id) {
+            if let Err(err) = sender
+              .send(GroupCommand::CollabDeleted {
+                object_id: object_id.clone(),
+              })
+              .await
+            {
+              error!("Send group command error: {}", err);
+            }
+          }
+        },
       }
     }
   });
@@ -1,6 +1,6 @@
 use crate::error::RealtimeError;
 use crate::group::group_init::EditState;
-use crate::group::protocol::ServerSyncProtocol;
+use crate::group::protocol::{negotiate_encoder_version, ServerSyncProtocol};
 use crate::metrics::CollabMetricsCalculate;
 use anyhow::anyhow;
 use bytes::Bytes;
@@ -13,10 +13,13 @@ use collab_rt_entity::{
   AwarenessSync, BroadcastSync, ClientCollabMessage, CollabAck, CollabMessage,
 };
 use collab_rt_protocol::{handle_message_follow_protocol, RTProtocolError};
-use collab_rt_protocol::{Message, MessageReader, MSG_SYNC, MSG_SYNC_UPDATE};
+use collab_rt_protocol::{
+  CustomMessage, Message, MessageReader, SyncMessage, UpdateEncoderVersion, MSG_SYNC,
+  MSG_SYNC_UPDATE,
+};
 use futures_util::{SinkExt, StreamExt};
-use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use tokio::select;
 
@@ -25,9 +28,9 @@ use tokio::sync::broadcast::{channel, Sender};
 use tokio::time::{sleep, Instant};
 use tracing::{error, trace, warn};
 use yrs::encoding::write::Write;
-use yrs::updates::decoder::DecoderV1;
+use yrs::updates::decoder::{Decode, DecoderV1};
 use yrs::updates::encoder::{Encode, Encoder, EncoderV1};
-use yrs::Subscription as YrsSubscription;
+use yrs::{Subscription as YrsSubscription, Update};
 
 pub trait CollabUpdateStreaming: 'static + Send + Sync {
   fn send_update(&self, update: Vec<u8>) -> Result<(), RealtimeError>;
@@ -38,7 +41,7 @@ pub trait CollabUpdateStreaming: 'static + Send + Sync {
 ///
 pub struct CollabBroadcast {
   object_id: String,
-  broadcast_sender: Sender<CollabMessage>,
+  broadcast_sender: Sender<BroadcastMessage>,
   awareness_sub: Option<YrsSubscription>,
   /// Keep the lifetime of the document observer subscription. The subscription will be stopped
   /// when the broadcast is dropped.
@@ -120,7 +123,7 @@ impl CollabBroadcast {
           }
           let payload = gen_update_message(&event.update);
           let msg = BroadcastSync::new(origin, cloned_oid.clone(), payload, seq_num);
-          if let Err(err) = broadcast_sink.send(msg.into()) {
+          if let Err(err) = broadcast_sink.send(BroadcastMessage::new(msg.into())) {
             trace!("fail to broadcast updates:{}", err);
           }
           *modified_at.lock() = Instant::now();
@@ -135,7 +138,7 @@ impl CollabBroadcast {
         if let Some(awareness_update) = event.awareness_update() {
           let payload = Message::Awareness(awareness_update.clone()).encode_v1();
           let msg = AwarenessSync::new(cloned_oid.clone(), payload, CollabOrigin::Empty);
-          if let Err(err) = broadcast_sink.send(msg.into()) {
+          if let Err(err) = broadcast_sink.send(BroadcastMessage::new(msg.into())) {
             trace!("fail to broadcast awareness:{}", err);
           }
         }
@@ -189,6 +192,9 @@ impl CollabBroadcast {
     Stream: StreamExt<Item = MessageByObjectId> + Send + Sync + Unpin + 'static,
     <Sink as futures_util::Sink<CollabMessage>>::Error: std::error::Error + Send + Sync,
   {
+    // The encoder version of the updates broadcast to the subscriber, negotiated during its init
+    // sync.
+    let encoder_version = Arc::new(AtomicU8::new(UpdateEncoderVersion::V1 as u8));
     let sink_stop_tx = {
       let mut sink = sink.clone();
       let (stop_tx, mut stop_rx) = tokio::sync::mpsc::channel::<()>(1);
@@ -197,6 +203,7 @@ impl CollabBroadcast {
       // connected subscriber using its Sink. The loop will break if the stop_rx receives a message.
       let mut receiver = self.broadcast_sender.subscribe();
       let cloned_user = user.clone();
+      let encoder_version = encoder_version.clone();
       tokio::spawn(async move {
         loop {
           select! {
@@ -206,10 +213,13 @@ impl CollabBroadcast {
                 Ok(message) => {
 
                   // No need to broadcast the message back to the originator
-                  if message.origin() == &subscriber_origin {
+                  if message.message.origin() == &subscriber_origin {
                     continue;
                   }
 
+                  let version = UpdateEncoderVersion::try_from(encoder_version.load(Ordering::SeqCst))
+                    .unwrap_or_default();
+                  let message = message.encode_with_version(version);
                   trace!("[realtime]: send {} => {}", message, cloned_user.user_device());
                   if let Err(err) = sink.send(message).await {
                     error!("fail to broadcast message:{}", err);
@@ -253,7 +263,7 @@ impl CollabBroadcast {
                   break
                 },
                 Some(collab) => {
-                  handle_client_messages(&object_id, message_map, &mut sink, collab, &metrics_calculate, &edit_state).await;
+                  handle_client_messages(&object_id, message_map, &mut sink, collab, &metrics_calculate, &edit_state, &encoder_version).await;
                 }
               }
             }
@@ -269,6 +279,29 @@ impl CollabBroadcast {
       stream_stop_tx: Some(stream_stop_tx),
     }
   }
+
+  /// Asks every subscriber of this broadcast to discard its sync state and perform a fresh init
+  /// sync. The message is sent with the [CollabOrigin::Server] origin, so it is delivered to all
+  /// connected clients.
+  pub fn force_resync(&self) {
+    let ack = CollabAck::new(CollabOrigin::Server, self.object_id.clone(), 0, 0)
+      .with_code(AckCode::ForceResync);
+    let msg = BroadcastMessage::new(ack.into());
+    if let Err(err) = self.broadcast_sender.send(msg) {
+      trace!("fail to broadcast force resync:{}", err);
+    }
+  }
+
+  /// Tells every subscriber of this broadcast that the collab has been deleted, so they stop
+  /// syncing it.
+  pub fn broadcast_deleted(&self) {
+    let ack = CollabAck::new(CollabOrigin::Server, self.object_id.clone(), 0, 0)
+      .with_code(AckCode::CollabDeleted);
+    let msg = BroadcastMessage::new(ack.into());
+    if let Err(err) = self.broadcast_sender.send(msg) {
+      trace!("fail to broadcast collab deleted:{}", err);
+    }
+  }
 }
 
 async fn handle_client_messages<Sink>(
@@ -278,6 +311,7 @@ async fn handle_client_messages<Sink>(
   collab: MutexCollab,
   metrics_calculate: &CollabMetricsCalculate,
   edit_state: &Arc<EditState>,
+  encoder_version: &Arc<AtomicU8>,
 ) where
   Sink: SinkExt<CollabMessage> + Unpin + 'static,
   <Sink as futures_util::Sink<CollabMessage>>::Error: std::error::Error,
@@ -304,6 +338,7 @@ async fn handle_client_messages<Sink>(
         &collab,
         metrics_calculate,
         edit_state,
+        encoder_version,
       )
       .await
       {
@@ -336,6 +371,7 @@ async fn handle_one_client_message(
   collab: &MutexCollab,
   metrics_calculate: &CollabMetricsCalculate,
   edit_state: &Arc<EditState>,
+  encoder_version: &Arc<AtomicU8>,
 ) -> Result<CollabAck, RealtimeError> {
   let msg_id = collab_msg.msg_id();
   let message_origin = collab_msg.origin().clone();
@@ -376,6 +412,7 @@ async fn handle_one_client_message(
       collab,
       metrics_calculate,
       seq_num,
+      encoder_version,
     )
     .await
     {
@@ -401,6 +438,7 @@ async fn handle_one_client_message(
 }
 
 /// Handle the message sent from the client
+#[allow(clippy::too_many_arguments)]
 async fn handle_one_message_payload(
   object_id: &str,
   message_origin: CollabOrigin,
@@ -409,6 +447,7 @@ async fn handle_one_message_payload(
   collab: &MutexCollab,
   metrics_calculate: &CollabMetricsCalculate,
   seq_num: u32,
+  encoder_version: &Arc<AtomicU8>,
 ) -> Result<CollabAck, RealtimeError> {
   let payload = payload.clone();
   metrics_calculate
@@ -419,6 +458,7 @@ async fn handle_one_message_payload(
   let mutex_collab = collab.clone();
   let metrics_calculate = metrics_calculate.clone();
   let cloned_collab_origin = message_origin.clone();
+  let encoder_version = encoder_version.clone();
 
   // Spawn a blocking task to handle the message
   let result = tokio::task::spawn_blocking(move || {
@@ -437,6 +477,10 @@ async fn handle_one_message_payload(
     for msg in reader {
       match msg {
         Ok(msg) => {
+          if let Message::Custom(CustomMessage::EncoderVersion(client_version)) = &msg {
+            let version = negotiate_encoder_version(*client_version);
+            encoder_version.store(version as u8, Ordering::SeqCst);
+          }
           match handle_message_follow_protocol(
             &message_origin,
             &ServerSyncProtocol,
@@ -448,17 +492,25 @@ async fn handle_one_message_payload(
                 .apply_update_count
                 .fetch_add(1, Ordering::Relaxed);
               // One ClientCollabMessage can have multiple Yrs [Message] in it, but we only need to
-              // send one ack back to the client.
-              if ack_response.is_none() {
-                ack_response = Some(
-                  CollabAck::new(
-                    message_origin.clone(),
-                    cloned_object_id.to_string(),
-                    msg_id,
-                    seq_num,
-                  )
-                  .with_payload(payload.unwrap_or_default()),
-                );
+              // send one ack back to the client. The replies of the following messages, such as
+              // the negotiated encoder version, are appended to the payload of the ack.
+              match ack_response.as_mut() {
+                None => {
+                  ack_response = Some(
+                    CollabAck::new(
+                      message_origin.clone(),
+                      cloned_object_id.to_string(),
+                      msg_id,
+                      seq_num,
+                    )
+                    .with_payload(payload.unwrap_or_default()),
+                  );
+                },
+                Some(ack) => {
+                  if let Some(payload) = payload {
+                    ack.payload = Bytes::from([ack.payload.as_ref(), &payload].concat());
+                  }
+                },
               }
             },
             Err(err) => {
@@ -577,6 +629,79 @@ fn gen_update_message(update: &[u8]) -> Vec<u8> {
   encoder.to_vec()
 }
 
+/// A message sent to the subscribers of a [CollabBroadcast]. The message encoded with v2 is
+/// shared by the subscribers that negotiated v2, so that it is encoded once per broadcast.
+#[derive(Clone)]
+struct BroadcastMessage {
+  message: CollabMessage,
+  v2_message: Arc<OnceLock<CollabMessage>>,
+}
+
+impl BroadcastMessage {
+  fn new(message: CollabMessage) -> Self {
+    Self {
+      message,
+      v2_message: Arc::new(OnceLock::new()),
+    }
+  }
+
+  fn encode_with_version(&self, version: UpdateEncoderVersion) -> CollabMessage {
+    match version {
+      UpdateEncoderVersion::V1 => self.message.clone(),
+      UpdateEncoderVersion::V2 => self
+        .v2_message
+        .get_or_init(|| encode_broadcast_with_version(self.message.clone(), version))
+        .clone(),
+    }
+  }
+}
+
+/// Encodes the updates of a [BroadcastSync] with the encoder version negotiated with the
+/// subscriber. The payload starts with the version, so the subscriber decodes the updates with it
+/// even if the broadcast is received before the reply of the negotiation.
+fn encode_broadcast_with_version(
+  message: CollabMessage,
+  version: UpdateEncoderVersion,
+) -> CollabMessage {
+  if version == UpdateEncoderVersion::V1 {
+    return message;
+  }
+
+  let (origin, seq_num) = match &message {
+    CollabMessage::ServerBroadcast(broadcast) => (broadcast.origin.clone(), broadcast.seq_num),
+    _ => return message,
+  };
+  match encode_update_message_with_version(message.payload(), version) {
+    Ok(payload) => {
+      BroadcastSync::new(origin, message.object_id().to_string(), payload, seq_num).into()
+    },
+    Err(err) => {
+      // the broadcast is still sent with v1, which the subscriber decodes without a version
+      warn!("fail to encode broadcast with {:?}: {}", version, err);
+      message
+    },
+  }
+}
+
+fn encode_update_message_with_version(
+  payload: &[u8],
+  version: UpdateEncoderVersion,
+) -> Result<Vec<u8>, yrs::encoding::read::Error> {
+  let mut encoder = EncoderV1::new();
+  Message::Custom(CustomMessage::EncoderVersion(version)).encode(&mut encoder);
+  let mut decoder = DecoderV1::from(payload);
+  for msg in MessageReader::new(&mut decoder) {
+    match msg? {
+      Message::Sync(SyncMessage::Update(update)) => {
+        let update = version.encode_update(&Update::decode_v1(&update)?);
+        Message::Sync(SyncMessage::Update(update)).encode(&mut encoder);
+      },
+      msg => msg.encode(&mut encoder),
+    }
+  }
+  Ok(encoder.to_vec())
+}
+
 #[inline]
 fn update_last_sync_at(collab: &MutexCollab) {
   if let Some(collab) = collab.try_lock() {
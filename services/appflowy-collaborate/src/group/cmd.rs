@@ -19,6 +19,8 @@ use crate::group::manager::GroupManager;
 /// Using [GroupCommand] to interact with the group
 /// - HandleClientCollabMessage: Handle the client message
 /// - EncodeCollab: Encode the collab
+/// - ForceResync: Ask all the subscribers of the group to perform a fresh init sync
+/// - CollabDeleted: Tell all the subscribers of the group that the collab has been deleted
 pub enum GroupCommand {
   HandleClientCollabMessage {
     user: RealtimeUser,
@@ -30,6 +32,13 @@ pub enum GroupCommand {
     object_id: String,
     ret: tokio::sync::oneshot::Sender<Option<EncodedCollab>>,
   },
+  ForceResync {
+    object_id: String,
+    workspace_id: String,
+  },
+  CollabDeleted {
+    object_id: String,
+  },
 }
 
 pub type GroupCommandSender = tokio::sync::mpsc::Sender<GroupCommand>;
@@ -88,6 +97,23 @@ where
               warn!("Send encode collab fail");
             }
           },
+          GroupCommand::ForceResync {
+            object_id,
+            workspace_id,
+          } => {
+            if let Some(group) = self.group_manager.get_group(&object_id).await {
+              if group.workspace_id == workspace_id {
+                trace!("force resync collab group:{}", object_id);
+                group.force_resync();
+              }
+            }
+          },
+          GroupCommand::CollabDeleted { object_id } => {
+            if let Some(group) = self.group_manager.get_group(&object_id).await {
+              trace!("notify collab group:{} deleted", object_id);
+              group.notify_deleted();
+            }
+          },
         }
       })
       .await;
@@ -120,6 +120,16 @@ impl CollabGroup {
     Ok(encode_collab)
   }
 
+  /// Asks all the subscribers of this group to perform a fresh init sync.
+  pub fn force_resync(&self) {
+    self.broadcast.force_resync();
+  }
+
+  /// Tells all the subscribers of this group that the collab has been deleted.
+  pub fn notify_deleted(&self) {
+    self.broadcast.broadcast_deleted();
+  }
+
   pub fn contains_user(&self, user: &RealtimeUser) -> bool {
     self.subscribers.contains_key(user)
   }
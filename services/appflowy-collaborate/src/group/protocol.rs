@@ -3,11 +3,22 @@ use collab::core::collab::{TransactionExt, TransactionMutExt};
 use collab::core::origin::CollabOrigin;
 
 use collab_rt_protocol::CollabSyncProtocol;
-use collab_rt_protocol::{CustomMessage, Message, RTProtocolError, SyncMessage};
+use collab_rt_protocol::{
+  CustomMessage, Message, RTProtocolError, SyncMessage, UpdateEncoderVersion,
+};
 
 use yrs::updates::encoder::{Encode, Encoder, EncoderV1};
 use yrs::{ReadTxn, StateVector, Transact, Update};
 
+/// The highest encoder version of the updates broadcast by the server.
+const SERVER_MAX_ENCODER_VERSION: UpdateEncoderVersion = UpdateEncoderVersion::V2;
+
+/// Returns the encoder version of the updates broadcast to a client that can decode the updates
+/// encoded with `client_version`.
+pub fn negotiate_encoder_version(client_version: UpdateEncoderVersion) -> UpdateEncoderVersion {
+  client_version.min(SERVER_MAX_ENCODER_VERSION)
+}
+
 #[derive(Clone)]
 pub struct ServerSyncProtocol;
 impl CollabSyncProtocol for ServerSyncProtocol {
@@ -79,8 +90,16 @@ impl CollabSyncProtocol for ServerSyncProtocol {
   fn handle_custom_message(
     &self,
     _awareness: &mut Awareness,
-    _msg: CustomMessage,
+    msg: CustomMessage,
   ) -> Result<Option<Vec<u8>>, RTProtocolError> {
-    Ok(None)
+    match msg {
+      CustomMessage::SyncCheck(_) => Ok(None),
+      CustomMessage::EncoderVersion(client_version) => {
+        let version = negotiate_encoder_version(client_version);
+        Ok(Some(
+          Message::Custom(CustomMessage::EncoderVersion(version)).encode_v1(),
+        ))
+      },
+    }
   }
 }
@@ -66,6 +66,34 @@ pub fn device_id_from_headers(headers: &HeaderMap) -> Result<String, AppError> {
     .map(|s| s.to_string())
 }
 
+#[inline]
+pub fn accept_language_from_headers(headers: &HeaderMap) -> Option<&str> {
+  headers
+    .get(actix_http::header::ACCEPT_LANGUAGE)
+    .and_then(|header| header.to_str().ok())
+}
+
+pub const X_IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// Returns the idempotency key of the request, which lets the client retry a request without the
+/// risk of applying it twice.
+pub fn idempotency_key_from_headers(headers: &HeaderMap) -> Result<Option<&str>, AppError> {
+  match headers.get(X_IDEMPOTENCY_KEY) {
+    None => Ok(None),
+    Some(header) => {
+      let key = header.to_str().map_err(|err| {
+        AppError::InvalidRequest(format!("Failed to parse idempotency key: {}", err))
+      })?;
+      if key.is_empty() || key.len() > 255 {
+        return Err(AppError::InvalidRequest(
+          "Idempotency key must be between 1 and 255 characters long".to_string(),
+        ));
+      }
+      Ok(Some(key))
+    },
+  }
+}
+
 #[async_trait]
 pub trait CollabValidator {
   async fn check_encode_collab(&self) -> Result<(), AppError>;
@@ -1,13 +1,15 @@
 use crate::api::util::PayloadReader;
+use actix_web::http::header;
 use actix_web::web::{Bytes, Payload};
 use actix_web::web::{Data, Json, PayloadConfig};
 use actix_web::{web, Scope};
-use actix_web::{HttpRequest, Result};
+use actix_web::{HttpRequest, HttpResponse, Result};
 use anyhow::{anyhow, Context};
 use bytes::BytesMut;
 use collab_entity::CollabType;
 use prost::Message as ProstMessage;
 use sqlx::types::uuid;
+use std::net::SocketAddr;
 use tokio::time::Instant;
 use tokio_stream::StreamExt;
 use tokio_tungstenite::tungstenite::Message;
@@ -29,7 +31,10 @@ use shared_entity::dto::workspace_dto::*;
 use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
 
-use crate::api::util::{compress_type_from_header_value, device_id_from_headers, CollabValidator};
+use crate::api::util::{
+  accept_language_from_headers, compress_type_from_header_value, device_id_from_headers,
+  idempotency_key_from_headers, CollabValidator,
+};
 use crate::api::ws::RealtimeServerAddr;
 use crate::biz;
 use crate::biz::workspace;
@@ -46,6 +51,12 @@ pub const COLLAB_PATTERN: &str = "/api/workspace/{workspace_id}/collab/{object_i
 pub const V1_COLLAB_PATTERN: &str = "/api/workspace/v1/{workspace_id}/collab/{object_id}";
 pub const WORKSPACE_PUBLISH_NAMESPACE_PATTERN: &str =
   "/api/workspace/{workspace_id}/publish-namespace";
+pub const WORKSPACE_PUBLISH_ALL_PATTERN: &str = "/api/workspace/{workspace_id}/publish/all";
+pub const WORKSPACE_PUBLISH_AUDIT_LOG_PATTERN: &str =
+  "/api/workspace/{workspace_id}/publish/{view_id}/audit-log";
+pub const WORKSPACE_PUBLISH_STATS_PATTERN: &str =
+  "/api/workspace/{workspace_id}/publish/{view_id}/stats";
+pub const WORKSPACE_FORCE_RESYNC_PATTERN: &str = "/api/workspace/{workspace_id}/force-resync";
 
 pub fn workspace_scope() -> Scope {
   web::scope("/api/workspace")
@@ -66,6 +77,14 @@ pub fn workspace_scope() -> Scope {
       web::resource("/accept-invite/{invite_id}")
         .route(web::post().to(post_accept_workspace_invite_handler)) // accept invitation to workspace
     )
+    .service(
+      web::resource("/publish-namespace-rules")
+        .route(web::get().to(get_publish_namespace_rules_handler))
+    )
+    .service(
+      web::resource("/my-published-views")
+        .route(web::get().to(get_my_published_views_handler))
+    )
     .service(web::resource("/{workspace_id}")
       .route(web::delete().to(delete_workspace_handler))
     )
@@ -75,6 +94,10 @@ pub fn workspace_scope() -> Scope {
     )
     .service(web::resource("/{workspace_id}/open").route(web::put().to(open_workspace_handler)))
     .service(web::resource("/{workspace_id}/leave").route(web::post().to(leave_workspace_handler)))
+    .service(
+      web::resource("/{workspace_id}/force-resync")
+        .route(web::post().to(post_force_resync_workspace_handler))
+    )
     .service(
       web::resource("/{workspace_id}/member")
       .route(web::get().to(get_workspace_members_handler))
@@ -127,6 +150,10 @@ pub fn workspace_scope() -> Scope {
         .route(web::put().to(update_collab_member_handler))
         .route(web::delete().to(remove_collab_member_handler)),
     )
+    .service(
+      web::resource("/published/{publish_namespace}")
+        .route(web::get().to(get_namespace_published_views_handler))
+    )
     .service(
       web::resource("/published/{publish_namespace}/{publish_name}")
         .route(web::get().to(get_published_collab_handler))
@@ -135,6 +162,10 @@ pub fn workspace_scope() -> Scope {
       web::resource("/published/{publish_namespace}/{publish_name}/blob")
         .route(web::get().to(get_published_collab_blob_handler))
     )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/og")
+        .route(web::get().to(get_published_og_metadata_handler))
+    )
     .service(
       web::resource("/published-info/{view_id}")
         .route(web::get().to(get_published_collab_info_handler))
@@ -149,6 +180,42 @@ pub fn workspace_scope() -> Scope {
         .route(web::post().to(post_publish_collabs_handler))
         .route(web::delete().to(delete_published_collabs_handler))
     )
+    .service(
+      web::resource("/{workspace_id}/publish/stream")
+        .route(web::post().to(post_publish_collabs_stream_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/repoint")
+        .route(web::put().to(put_repoint_published_view_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/all")
+        .route(web::delete().to(delete_all_published_collabs_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/databases")
+        .route(web::get().to(list_published_databases_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/name")
+        .route(web::put().to(put_publish_name_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/versions")
+        .route(web::get().to(list_publish_versions_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/versions/{version}/restore")
+        .route(web::post().to(post_restore_publish_version_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/audit-log")
+        .route(web::get().to(get_publish_audit_log_handler))
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/stats")
+        .route(web::get().to(get_published_view_stats_handler))
+    )
     .service(
       web::resource("/{workspace_id}/collab/{object_id}/member/list")
         .route(web::get().to(get_collab_member_list_handler)),
@@ -413,6 +480,19 @@ async fn open_workspace_handler(
   Ok(AppResponse::Ok().with_data(workspace).into())
 }
 
+#[instrument(level = "debug", skip_all, err)]
+async fn post_force_resync_workspace_handler(
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<()>> {
+  let workspace_id = workspace_id.into_inner();
+  state
+    .collab_access_control_storage
+    .force_resync_workspace(&workspace_id.to_string())
+    .await?;
+  Ok(AppResponse::Ok().into())
+}
+
 #[instrument(level = "debug", skip_all, err)]
 async fn leave_workspace_handler(
   user_uuid: UserUuid,
@@ -945,9 +1025,13 @@ async fn put_publish_namespace_handler(
   workspace_id: web::Path<Uuid>,
   payload: Json<UpdatePublishNamespace>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<()>>> {
+) -> Result<Json<AppResponse<PublishNamespaceInvalidReason>>> {
   let workspace_id = workspace_id.into_inner();
   let new_namespace = payload.into_inner().new_namespace;
+  if let Err(reason) = biz::workspace::ops::check_workspace_namespace(&new_namespace) {
+    let err = AppError::InvalidRequest(reason.to_string());
+    return Ok(Json(AppResponse::from(err).with_data(reason)));
+  }
   biz::workspace::ops::set_workspace_namespace(
     &state.pg_pool,
     &user_uuid,
@@ -968,38 +1052,298 @@ async fn get_publish_namespace_handler(
   Ok(Json(AppResponse::Ok().with_data(namespace)))
 }
 
+async fn get_publish_namespace_rules_handler() -> Result<Json<AppResponse<PublishNamespaceRules>>> {
+  Ok(Json(
+    AppResponse::Ok().with_data(PublishNamespaceRules::default()),
+  ))
+}
+
+async fn get_my_published_views_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishedViewOfUser>>>> {
+  let views = biz::workspace::ops::get_published_views_of_user(&state.pg_pool, &user_uuid).await?;
+  Ok(Json(AppResponse::Ok().with_data(views)))
+}
+
 async fn get_published_collab_handler(
   path_param: web::Path<(String, String)>,
+  user_uuid: Option<UserUuid>,
   state: Data<AppState>,
+  req: HttpRequest,
 ) -> Result<Json<serde_json::Value>> {
   let (workspace_namespace, publish_name) = path_param.into_inner();
-  let metadata =
-    biz::workspace::ops::get_published_collab(&state.pg_pool, &workspace_namespace, &publish_name)
-      .await?;
+  check_published_view_access(&state, &workspace_namespace, user_uuid).await?;
+  let metadata = biz::workspace::ops::get_published_collab(
+    &state.pg_pool,
+    &workspace_namespace,
+    &publish_name,
+    accept_language_from_headers(req.headers()),
+  )
+  .await?;
   Ok(Json(metadata))
 }
 
+async fn get_namespace_published_views_handler(
+  publish_namespace: web::Path<String>,
+  user_uuid: Option<UserUuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishedViewSummary>>>> {
+  let publish_namespace = publish_namespace.into_inner();
+  check_published_view_access(&state, &publish_namespace, user_uuid).await?;
+  let views =
+    biz::workspace::ops::get_namespace_published_views(&state.pg_pool, &publish_namespace).await?;
+  Ok(Json(AppResponse::Ok().with_data(views)))
+}
+
+/// Returns the blob of the published view. The view is counted as read only when the blob is
+/// sent, a revalidation answered with `304 Not Modified` doesn't count.
 async fn get_published_collab_blob_handler(
   path_param: web::Path<(String, String)>,
+  user_uuid: Option<UserUuid>,
   state: Data<AppState>,
-) -> Result<Vec<u8>> {
+  req: HttpRequest,
+) -> Result<HttpResponse> {
   let (publish_namespace, publish_name) = path_param.into_inner();
+  let uid = check_published_view_access(&state, &publish_namespace, user_uuid).await?;
+  let etag = format!(
+    "\"{}\"",
+    biz::workspace::ops::get_published_collab_blob_etag(
+      &state.pg_pool,
+      &publish_namespace,
+      &publish_name,
+    )
+    .await?
+  );
+  if if_none_match_contains(&req, &etag) {
+    return Ok(
+      HttpResponse::NotModified()
+        .insert_header((header::ETAG, etag))
+        .finish(),
+    );
+  }
+
   let collab_data = biz::workspace::ops::get_published_collab_blob(
     &state.pg_pool,
+    &state.published_blob_storage,
     &publish_namespace,
     &publish_name,
   )
   .await?;
-  Ok(collab_data)
+  biz::workspace::ops::record_published_view_visit(
+    &state.pg_pool,
+    &publish_namespace,
+    &publish_name,
+    published_view_visitor_key(&req, uid),
+    uid,
+  );
+  Ok(
+    HttpResponse::Ok()
+      .content_type(mime::APPLICATION_OCTET_STREAM)
+      .insert_header((header::ETAG, etag))
+      .body(collab_data),
+  )
+}
+
+/// Whether the `If-None-Match` header of the request matches the ETag. Weak ETags are compared as
+/// strong ones, since the blob is the only representation of a published view.
+fn if_none_match_contains(req: &HttpRequest, etag: &str) -> bool {
+  req
+    .headers()
+    .get_all(header::IF_NONE_MATCH)
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .map(|value| value.trim().trim_start_matches("W/"))
+    .any(|value| value == "*" || value == etag)
+}
+
+/// Published views can be read by guests, so the user is optional. Whether a guest or a user can
+/// read them depends on the settings of the workspace. Returns the uid of the user, if any.
+async fn check_published_view_access(
+  state: &AppState,
+  publish_namespace: &str,
+  user_uuid: Option<UserUuid>,
+) -> Result<Option<i64>, AppResponseError> {
+  let uid = match user_uuid {
+    Some(user_uuid) => Some(state.user_cache.get_user_uid(&user_uuid).await?),
+    None => None,
+  };
+  biz::workspace::ops::check_published_view_access(&state.pg_pool, publish_namespace, uid).await?;
+  Ok(uid)
+}
+
+async fn check_published_view_access_for_view_id(
+  state: &AppState,
+  view_id: &Uuid,
+  user_uuid: Option<UserUuid>,
+) -> Result<(), AppResponseError> {
+  let uid = match user_uuid {
+    Some(user_uuid) => Some(state.user_cache.get_user_uid(&user_uuid).await?),
+    None => None,
+  };
+  biz::workspace::ops::check_published_view_access_for_view_id(&state.pg_pool, view_id, uid)
+    .await?;
+  Ok(())
+}
+
+/// Identifies the visitor of a published view: the user if logged in, the ip address otherwise.
+fn published_view_visitor_key(req: &HttpRequest, uid: Option<i64>) -> Option<String> {
+  if let Some(uid) = uid {
+    return Some(format!("uid:{}", uid));
+  }
+  let conn_info = req.connection_info();
+  let addr = conn_info.realip_remote_addr()?;
+  let ip = addr
+    .parse::<SocketAddr>()
+    .map(|addr| addr.ip().to_string())
+    .unwrap_or_else(|_| addr.to_string());
+  Some(format!("ip:{}", ip))
+}
+
+async fn get_published_og_metadata_handler(
+  path_param: web::Path<(String, String)>,
+  user_uuid: Option<UserUuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishOgMetadata>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  check_published_view_access(&state, &publish_namespace, user_uuid).await?;
+  let og_metadata = biz::workspace::ops::get_published_og_metadata(
+    &state.pg_pool,
+    &state.published_blob_storage,
+    &publish_namespace,
+    &publish_name,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(og_metadata)))
+}
+
+async fn put_repoint_published_view_handler(
+  workspace_id: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  payload: Json<RepointPublishedView>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let workspace_id = workspace_id.into_inner();
+  let RepointPublishedView {
+    publish_name,
+    new_view_id,
+  } = payload.into_inner();
+  biz::workspace::ops::repoint_published_view(
+    &state.pg_pool,
+    &workspace_id,
+    &publish_name,
+    &new_view_id,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn put_publish_name_handler(
+  path_param: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  payload: Json<UpdatePublishName>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  biz::workspace::ops::rename_published_view(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+    &payload.new_publish_name,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_publish_versions_handler(
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishVersion>>>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let versions =
+    biz::workspace::ops::list_publish_versions(&state.pg_pool, &workspace_id, &view_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(versions)))
+}
+
+async fn post_restore_publish_version_handler(
+  path_param: web::Path<(Uuid, Uuid, i64)>,
+  query: web::Query<RestorePublishVersionQuery>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id, version) = path_param.into_inner();
+  biz::workspace::ops::restore_publish_version(
+    &state.pg_pool,
+    &state.published_blob_storage,
+    &workspace_id,
+    &view_id,
+    version,
+    query.latest_version,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_published_view_stats_handler(
+  path_param: web::Path<(Uuid, Uuid)>,
+  query: web::Query<PublishedViewStatsQuery>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedViewStats>>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let stats = biz::workspace::ops::get_published_view_stats(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+    query.exclude_owner,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(stats)))
+}
+
+async fn get_publish_audit_log_handler(
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishAuditLogEntry>>>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let audit_log =
+    biz::workspace::ops::get_publish_audit_log(&state.pg_pool, &workspace_id, &view_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(audit_log)))
+}
+
+async fn list_published_databases_handler(
+  workspace_id: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishedDatabase>>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let published_databases = biz::workspace::ops::list_workspace_published_databases(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    &uid,
+    &workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(published_databases)))
 }
 
 async fn get_published_collab_info_handler(
   view_id: web::Path<Uuid>,
+  user_uuid: Option<UserUuid>,
   state: Data<AppState>,
+  req: HttpRequest,
 ) -> Result<Json<AppResponse<PublishInfo>>> {
   let view_id = view_id.into_inner();
-  let collab_data =
-    biz::workspace::ops::get_published_collab_info(&state.pg_pool, &view_id).await?;
+  check_published_view_access_for_view_id(&state, &view_id, user_uuid).await?;
+  let collab_data = biz::workspace::ops::get_published_collab_info(
+    &state.pg_pool,
+    &view_id,
+    accept_language_from_headers(req.headers()),
+  )
+  .await?;
   Ok(Json(AppResponse::Ok().with_data(collab_data)))
 }
 
@@ -1008,48 +1352,116 @@ async fn post_publish_collabs_handler(
   user_uuid: UserUuid,
   payload: Payload,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<()>>> {
+  req: HttpRequest,
+) -> Result<Json<AppResponse<Vec<Uuid>>>> {
   let workspace_id = workspace_id.into_inner();
+  let idempotency_key = idempotency_key_from_headers(req.headers())?;
 
   let mut accumulator = Vec::<PublishCollabItem<serde_json::Value, Vec<u8>>>::new();
   let mut payload_reader: PayloadReader = PayloadReader::new(payload);
+  while let Some(item) = read_publish_collab_item(&mut payload_reader).await? {
+    accumulator.push(item);
+  }
 
-  loop {
-    let meta: PublishCollabMetadata<serde_json::Value> = {
-      let meta_len = payload_reader.read_u32_little_endian().await?;
-      if meta_len > 4 * 1024 * 1024 {
-        // 4MB Limit for metadata
-        return Err(AppError::InvalidRequest(String::from("metadata too large")).into());
-      }
-      if meta_len == 0 {
-        break;
-      }
+  // Nothing is written until the whole batch is received. If the client cancels the request,
+  // reading the payload fails before the terminating zero length and no collab is published.
+  if accumulator.is_empty() {
+    return Ok(Json(AppResponse::Ok().with_data(vec![])));
+  }
+  let published_view_ids = biz::workspace::ops::publish_collabs(
+    &state.pg_pool,
+    &state.published_blob_storage,
+    &workspace_id,
+    &user_uuid,
+    accumulator,
+    idempotency_key,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(published_view_ids)))
+}
 
-      let mut meta_buffer = vec![0; meta_len as usize];
-      payload_reader.read_exact(&mut meta_buffer).await?;
-      serde_json::from_slice(&meta_buffer)?
-    };
+/// The maximum number of items, and of bytes of data, published at once by
+/// [post_publish_collabs_stream_handler].
+pub const PUBLISH_STREAM_BATCH_MAX_ITEMS: usize = 100;
+pub const PUBLISH_STREAM_BATCH_MAX_BYTES: usize = 16 * 1024 * 1024;
 
-    let data = {
-      let data_len = payload_reader.read_u32_little_endian().await?;
-      if data_len > 128 * 1024 * 1024 {
-        // 128MB Limit for data
-        return Err(AppError::InvalidRequest(String::from("data too large")).into());
-      }
-      let mut data_buffer = vec![0; data_len as usize];
-      payload_reader.read_exact(&mut data_buffer).await?;
-      data_buffer
-    };
+/// Unlike [post_publish_collabs_handler], the items are published in bounded batches while the
+/// payload is read, so the memory used doesn't depend on the size of the payload. The batches are
+/// published independently: if the request fails, the batches published before are kept.
+async fn post_publish_collabs_stream_handler(
+  workspace_id: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  payload: Payload,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<Uuid>>>> {
+  let workspace_id = workspace_id.into_inner();
 
-    accumulator.push(PublishCollabItem { meta, data });
-  }
+  let mut published_view_ids = vec![];
+  let mut batch = Vec::<PublishCollabItem<serde_json::Value, Vec<u8>>>::new();
+  let mut batch_bytes = 0;
+  let mut payload_reader: PayloadReader = PayloadReader::new(payload);
+  loop {
+    let item = read_publish_collab_item(&mut payload_reader).await?;
+    let is_end = item.is_none();
+    if let Some(item) = item {
+      batch_bytes += item.data.len();
+      batch.push(item);
+    }
 
-  if accumulator.is_empty() {
-    return Ok(Json(AppResponse::Ok()));
+    let is_batch_full = batch.len() >= PUBLISH_STREAM_BATCH_MAX_ITEMS
+      || batch_bytes >= PUBLISH_STREAM_BATCH_MAX_BYTES;
+    if !batch.is_empty() && (is_batch_full || is_end) {
+      let view_ids = biz::workspace::ops::publish_collabs(
+        &state.pg_pool,
+        &state.published_blob_storage,
+        &workspace_id,
+        &user_uuid,
+        std::mem::take(&mut batch),
+        None,
+      )
+      .await?;
+      published_view_ids.extend(view_ids);
+      batch_bytes = 0;
+    }
+    if is_end {
+      break;
+    }
   }
-  biz::workspace::ops::publish_collabs(&state.pg_pool, &workspace_id, &user_uuid, &accumulator)
-    .await?;
-  Ok(Json(AppResponse::Ok()))
+  Ok(Json(AppResponse::Ok().with_data(published_view_ids)))
+}
+
+/// Reads the next item of a publish payload. Returns `None` when the terminating zero length is
+/// read.
+async fn read_publish_collab_item(
+  payload_reader: &mut PayloadReader,
+) -> Result<Option<PublishCollabItem<serde_json::Value, Vec<u8>>>> {
+  let meta: PublishCollabMetadata<serde_json::Value> = {
+    let meta_len = payload_reader.read_u32_little_endian().await?;
+    if meta_len > 4 * 1024 * 1024 {
+      // 4MB Limit for metadata
+      return Err(AppError::InvalidRequest(String::from("metadata too large")).into());
+    }
+    if meta_len == 0 {
+      return Ok(None);
+    }
+
+    let mut meta_buffer = vec![0; meta_len as usize];
+    payload_reader.read_exact(&mut meta_buffer).await?;
+    serde_json::from_slice(&meta_buffer)?
+  };
+
+  let data = {
+    let data_len = payload_reader.read_u32_little_endian().await?;
+    if data_len > 128 * 1024 * 1024 {
+      // 128MB Limit for data
+      return Err(AppError::InvalidRequest(String::from("data too large")).into());
+    }
+    let mut data_buffer = vec![0; data_len as usize];
+    payload_reader.read_exact(&mut data_buffer).await?;
+    data_buffer
+  };
+
+  Ok(Some(PublishCollabItem { meta, data }))
 }
 
 async fn delete_published_collabs_handler(
@@ -1057,20 +1469,37 @@ async fn delete_published_collabs_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
   view_ids: Json<Vec<Uuid>>,
-) -> Result<Json<AppResponse<()>>> {
+) -> Result<Json<AppResponse<Vec<UnpublishViewResult>>>> {
   let workspace_id = workspace_id.into_inner();
   let view_ids = view_ids.into_inner();
   if view_ids.is_empty() {
-    return Ok(Json(AppResponse::Ok()));
+    return Ok(Json(AppResponse::Ok().with_data(vec![])));
   }
-  biz::workspace::ops::delete_published_workspace_collab(
+  let results = biz::workspace::ops::delete_published_workspace_collab(
     &state.pg_pool,
+    &state.published_blob_storage,
     &workspace_id,
     &view_ids,
     &user_uuid,
   )
   .await?;
-  Ok(Json(AppResponse::Ok()))
+  Ok(Json(AppResponse::Ok().with_data(results)))
+}
+
+async fn delete_all_published_collabs_handler(
+  workspace_id: web::Path<Uuid>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<Uuid>>>> {
+  let workspace_id = workspace_id.into_inner();
+  let view_ids = biz::workspace::ops::delete_all_published_workspace_collabs(
+    &state.pg_pool,
+    &state.published_blob_storage,
+    &workspace_id,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(view_ids)))
 }
 
 #[instrument(level = "debug", skip(state, payload), err)]
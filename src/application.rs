@@ -12,6 +12,7 @@ use crate::api::history::history_scope;
 use crate::biz::collab::access_control::CollabMiddlewareAccessControl;
 use crate::biz::pg_listener::PgListeners;
 use crate::biz::workspace::access_control::WorkspaceMiddlewareAccessControl;
+use crate::biz::workspace::publish_blob::PublishedBlobStorage;
 use crate::config::config::{Config, DatabaseSetting, GoTrueSetting, S3Setting};
 use crate::middleware::access_control_mw::MiddlewareAccessControlTransform;
 use crate::middleware::metrics_mw::MetricsMiddleware;
@@ -209,6 +210,10 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     s3_client,
     pg_pool.clone(),
   ));
+  let published_blob_storage = Arc::new(PublishedBlobStorage::new(
+    bucket_storage.clone(),
+    config.publish.clone(),
+  ));
 
   // Gotrue
   info!("Connecting to GoTrue...");
@@ -302,6 +307,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     collab_access_control,
     workspace_access_control,
     bucket_storage,
+    published_blob_storage,
     pg_listeners,
     access_control,
     metrics,
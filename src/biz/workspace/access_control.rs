@@ -20,8 +20,9 @@ use database::user::select_uid_from_uuid;
 use database_entity::dto::AFRole;
 
 use crate::api::workspace::{
-  WORKSPACE_INVITE_PATTERN, WORKSPACE_MEMBER_PATTERN, WORKSPACE_PATTERN,
-  WORKSPACE_PUBLISH_NAMESPACE_PATTERN,
+  WORKSPACE_FORCE_RESYNC_PATTERN, WORKSPACE_INVITE_PATTERN, WORKSPACE_MEMBER_PATTERN,
+  WORKSPACE_PATTERN, WORKSPACE_PUBLISH_ALL_PATTERN, WORKSPACE_PUBLISH_AUDIT_LOG_PATTERN,
+  WORKSPACE_PUBLISH_NAMESPACE_PATTERN, WORKSPACE_PUBLISH_STATS_PATTERN,
 };
 use crate::middleware::access_control_mw::{AccessResource, MiddlewareAccessControl};
 use crate::state::UserCache;
@@ -72,6 +73,26 @@ where
           ]
           .into(),
         ),
+        (
+          // Only the Owner can force all the clients of the workspace to resync
+          ResourceDef::new(WORKSPACE_FORCE_RESYNC_PATTERN),
+          [(Method::POST, AFRole::Owner)].into(),
+        ),
+        (
+          // Only the Owner can unpublish all the published views of the workspace
+          ResourceDef::new(WORKSPACE_PUBLISH_ALL_PATTERN),
+          [(Method::DELETE, AFRole::Owner)].into(),
+        ),
+        (
+          // Only the Owner can read the publish audit log
+          ResourceDef::new(WORKSPACE_PUBLISH_AUDIT_LOG_PATTERN),
+          [(Method::GET, AFRole::Owner)].into(),
+        ),
+        (
+          // Only the Owner can read the stats of the published views
+          ResourceDef::new(WORKSPACE_PUBLISH_STATS_PATTERN),
+          [(Method::GET, AFRole::Owner)].into(),
+        ),
       ],
       access_control,
     }
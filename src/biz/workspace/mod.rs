@@ -1,2 +1,4 @@
 pub mod access_control;
 pub mod ops;
+pub mod publish_blob;
+pub mod publish_og;
@@ -1,11 +1,24 @@
-use database_entity::dto::{AFWorkspaceSettingsChange, PublishCollabItem};
+use database_entity::dto::{AFWorkspaceSettingsChange, PublishCollabItem, PublishCollabMetadata};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
-use database_entity::dto::PublishInfo;
+use database_entity::dto::{
+  PublishAuditAction, PublishAuditLogEntry, PublishInfo, PublishNamespaceInvalidReason,
+  PublishNamespaceRules, PublishOgMetadata, PublishVersion, PublishedDatabase, PublishedViewOfUser,
+  PublishedViewStats, PublishedViewSummary, QueryCollabParams, UnpublishResult,
+  UnpublishViewResult,
+};
 use std::ops::DerefMut;
 use std::sync::Arc;
 
 use anyhow::Context;
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_entity::define::WORKSPACE_DATABASES;
+use collab_entity::CollabType;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use sqlx::{types::uuid, PgPool};
 use tracing::instrument;
 use uuid::Uuid;
@@ -13,26 +26,41 @@ use uuid::Uuid;
 use access_control::workspace::WorkspaceAccessControl;
 use app_error::{AppError, ErrorCode};
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
-use database::collab::upsert_collab_member_with_txn;
+use database::collab::{upsert_collab_member_with_txn, CollabStorage};
 use database::file::s3_client_impl::S3BucketStorage;
 use database::pg_row::{AFWorkspaceMemberRow, AFWorkspaceRow};
 
-use database::user::select_uid_from_email;
+use database::user::{select_uid_from_email, select_uid_from_uuid};
 use database::workspace::{
-  change_workspace_icon, delete_from_workspace, delete_published_collabs, delete_workspace_members,
-  get_invitation_by_id, insert_or_replace_publish_collab_metas, insert_user_workspace,
-  insert_workspace_invitation, rename_workspace, select_all_user_workspaces,
-  select_publish_collab_meta, select_published_collab_blob, select_published_collab_info,
-  select_user_is_collab_publisher_for_all_views, select_user_is_workspace_owner, select_workspace,
+  change_workspace_icon, delete_all_publish_collab_versions, delete_all_published_collabs,
+  delete_from_workspace, delete_old_publish_collab_versions, delete_publish_collab_versions,
+  delete_published_collabs, delete_workspace_members, get_invitation_by_id,
+  insert_or_replace_publish_collab_metas, insert_publish_audit_logs,
+  insert_publish_collab_versions, insert_publish_idempotency_result, insert_published_view_visit,
+  insert_user_workspace, insert_workspace_invitation, rename_workspace, select_all_user_workspaces,
+  select_collab_partition_key_in_workspace, select_latest_publish_collab_version_for_update,
+  select_namespace_published_view_summaries, select_publish_audit_log, select_publish_collab_meta,
+  select_publish_collab_version, select_publish_collab_versions, select_publish_idempotency_result,
+  select_published_blob_s3_keys_for_update, select_published_collab_blob,
+  select_published_collab_blob_etag, select_published_collab_info,
+  select_published_metadata_for_view_id, select_published_og_metadata,
+  select_published_view_id_for_publish_name, select_published_view_stats,
+  select_published_view_using_publish_name, select_published_views_of_user,
+  select_published_views_publisher, select_unchanged_published_view_ids,
+  select_unreferenced_published_blob_s3_keys, select_user_is_collab_publisher_for_all_views,
+  select_user_is_workspace_owner, select_user_role, select_view_is_published, select_workspace,
   select_workspace_invitations_for_user, select_workspace_member, select_workspace_member_list,
   select_workspace_publish_namespace, select_workspace_publish_namespace_exists,
-  select_workspace_settings, select_workspace_total_collab_bytes, update_updated_at_of_workspace,
+  select_workspace_published_collab_infos, select_workspace_settings,
+  select_workspace_settings_for_publish_namespace, select_workspace_settings_for_published_view,
+  select_workspace_total_collab_bytes, update_published_collab_publish_name,
+  update_published_collab_view_id, update_updated_at_of_workspace,
   update_workspace_invitation_set_status_accepted, update_workspace_publish_namespace,
   upsert_workspace_member, upsert_workspace_member_with_txn, upsert_workspace_settings,
 };
 use database_entity::dto::{
   AFAccessLevel, AFRole, AFWorkspace, AFWorkspaceInvitation, AFWorkspaceInvitationStatus,
-  AFWorkspaceSettings, WorkspaceUsage,
+  AFWorkspaceSettings, PublishedViewAccess, WorkspaceUsage,
 };
 use gotrue::params::{GenerateLinkParams, GenerateLinkType};
 use shared_entity::dto::workspace_dto::{
@@ -42,6 +70,8 @@ use shared_entity::response::AppResponseError;
 use workspace_template::document::get_started::GetStartedDocumentTemplate;
 
 use crate::biz::user::user_init::initialize_workspace_for_user;
+use crate::biz::workspace::publish_blob::PublishedBlobStorage;
+use crate::biz::workspace::publish_og::generate_publish_og_metadata;
 use crate::mailer::{Mailer, WorkspaceInviteMailerParam};
 use crate::state::GoTrueAdmin;
 
@@ -118,7 +148,9 @@ pub async fn set_workspace_namespace(
   new_namespace: &str,
 ) -> Result<(), AppError> {
   check_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
-  check_workspace_namespace(new_namespace).await?;
+  check_workspace_publishing_enabled(pg_pool, workspace_id).await?;
+  check_workspace_namespace(new_namespace)
+    .map_err(|reason| AppError::InvalidRequest(reason.to_string()))?;
   if select_workspace_publish_namespace_exists(pg_pool, workspace_id, new_namespace).await? {
     return Err(AppError::PublishNamespaceAlreadyTaken(
       "publish namespace is already taken".to_string(),
@@ -143,52 +175,783 @@ pub async fn get_workspace_publish_namespace(
   Ok(namespace)
 }
 
+/// The number of versions of the published content kept for each view.
+const PUBLISH_VERSION_HISTORY_LEN: i64 = 10;
+
+/// How long the result of a publish request sent with an idempotency key is kept.
+const PUBLISH_IDEMPOTENCY_KEY_EXPIRE_HOURS: i32 = 24;
+
+/// Publishes the collabs of the batch. The batch is all-or-nothing: the collabs are written in a
+/// single transaction, so either all of them are published or none of them are.
+/// The items that are already published with the same content hash are skipped. Returns the view
+/// ids of the items that were published.
+/// When an idempotency key is given and a previous request with the same key was processed, the
+/// result of that request is returned and nothing is published.
 pub async fn publish_collabs(
   pg_pool: &PgPool,
+  published_blob_storage: &PublishedBlobStorage,
   workspace_id: &Uuid,
   publisher_uuid: &Uuid,
-  publish_items: &[PublishCollabItem<serde_json::Value, Vec<u8>>],
-) -> Result<(), AppError> {
-  for publish_item in publish_items {
+  publish_items: Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>,
+  idempotency_key: Option<&str>,
+) -> Result<Vec<Uuid>, AppError> {
+  publish_collabs_if_latest(
+    pg_pool,
+    published_blob_storage,
+    workspace_id,
+    publisher_uuid,
+    publish_items,
+    idempotency_key,
+    None,
+  )
+  .await
+}
+
+/// Same as [publish_collabs]. When `latest_version` is given, the batch holds a single view, and
+/// the publish fails with [AppError::PublishVersionConflict] unless `latest_version` is still the
+/// latest version of the view in the transaction that publishes it.
+async fn publish_collabs_if_latest(
+  pg_pool: &PgPool,
+  published_blob_storage: &PublishedBlobStorage,
+  workspace_id: &Uuid,
+  publisher_uuid: &Uuid,
+  mut publish_items: Vec<PublishCollabItem<serde_json::Value, Vec<u8>>>,
+  idempotency_key: Option<&str>,
+  latest_version: Option<i64>,
+) -> Result<Vec<Uuid>, AppError> {
+  check_workspace_publishing_enabled(pg_pool, workspace_id).await?;
+  check_workspace_min_publish_role(pg_pool, workspace_id, publisher_uuid).await?;
+  if let Some(idempotency_key) = idempotency_key {
+    if let Some(published_view_ids) = select_publish_idempotency_result(
+      pg_pool,
+      workspace_id,
+      idempotency_key,
+      PUBLISH_IDEMPOTENCY_KEY_EXPIRE_HOURS,
+    )
+    .await?
+    {
+      return Ok(published_view_ids);
+    }
+  }
+
+  for publish_item in &publish_items {
     check_collab_publish_name(publish_item.meta.publish_name.as_str())?;
+    check_publish_content_hash(publish_item)?;
+  }
+  check_publish_names_in_batch(&publish_items)?;
+  let publish_names: Vec<String> = publish_items
+    .iter()
+    .map(|item| item.meta.publish_name.clone())
+    .collect();
+  let view_ids: Vec<Uuid> = publish_items.iter().map(|item| item.meta.view_id).collect();
+  if let Some((publish_name, view_id)) =
+    select_published_view_using_publish_name(pg_pool, workspace_id, &publish_names, &view_ids)
+      .await?
+  {
+    return Err(AppError::PublishNameAlreadyTaken(format!(
+      "publish name {} is already used by view {}",
+      publish_name, view_id
+    )));
+  }
+
+  let unchanged_view_ids =
+    select_unchanged_published_view_ids(pg_pool, workspace_id, &publish_items).await?;
+  publish_items.retain(|item| !unchanged_view_ids.contains(&item.meta.view_id));
+  if publish_items.is_empty() {
+    if let Some(idempotency_key) = idempotency_key {
+      let mut txn = pg_pool.begin().await?;
+      let recorded = insert_publish_idempotency_result(
+        &mut txn,
+        workspace_id,
+        idempotency_key,
+        &[],
+        PUBLISH_IDEMPOTENCY_KEY_EXPIRE_HOURS,
+      )
+      .await?;
+      txn.commit().await?;
+      if !recorded {
+        return get_recorded_publish_result(pg_pool, workspace_id, idempotency_key).await;
+      }
+    }
+    return Ok(vec![]);
   }
-  insert_or_replace_publish_collab_metas(pg_pool, workspace_id, publisher_uuid, publish_items)
+  let publish_items = publish_items.as_slice();
+
+  let og_metadatas = publish_items
+    .iter()
+    .map(|item| {
+      serde_json::to_value(generate_publish_og_metadata(
+        &item.meta.view_id.to_string(),
+        &item.meta.metadata,
+        &item.data,
+      ))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  // The ETag of the blob is computed from the data, the content hash of the publisher is optional
+  let blob_etags: Vec<String> = publish_items
+    .iter()
+    .map(|item| format!("{:x}", Sha256::digest(&item.data)))
+    .collect();
+  let view_ids: Vec<Uuid> = publish_items.iter().map(|item| item.meta.view_id).collect();
+
+  let blob_s3_keys = published_blob_storage
+    .put_blobs(workspace_id, publish_items)
     .await?;
-  Ok(())
+
+  let result = async {
+    let mut txn = pg_pool.begin().await?;
+    // The key is recorded first, so that a concurrent request with the same key waits for this
+    // one to finish and then returns its result.
+    if let Some(idempotency_key) = idempotency_key {
+      let recorded = insert_publish_idempotency_result(
+        &mut txn,
+        workspace_id,
+        idempotency_key,
+        &view_ids,
+        PUBLISH_IDEMPOTENCY_KEY_EXPIRE_HOURS,
+      )
+      .await?;
+      if !recorded {
+        return Ok(None);
+      }
+    }
+    if let Some(latest_version) = latest_version {
+      for view_id in &view_ids {
+        let current_latest_version =
+          select_latest_publish_collab_version_for_update(&mut txn, workspace_id, view_id).await?;
+        if current_latest_version != Some(latest_version) {
+          return Err(AppError::PublishVersionConflict(format!(
+            "the latest version of view {} is {:?}, not {}",
+            view_id, current_latest_version, latest_version
+          )));
+        }
+      }
+    }
+    // The blobs stored in the bucket by a previous publish of the views are replaced. They are
+    // still used by the versions of the views, until the versions are deleted.
+    let replaced_blob_s3_keys =
+      select_published_blob_s3_keys_for_update(&mut txn, workspace_id, &view_ids).await?;
+    insert_or_replace_publish_collab_metas(
+      txn.deref_mut(),
+      workspace_id,
+      publisher_uuid,
+      publish_items,
+      &og_metadatas,
+      &blob_s3_keys,
+      &blob_etags,
+    )
+    .await?;
+    insert_publish_collab_versions(
+      txn.deref_mut(),
+      workspace_id,
+      publisher_uuid,
+      publish_items,
+      &blob_s3_keys,
+    )
+    .await?;
+    let mut released_blob_s3_keys = delete_old_publish_collab_versions(
+      txn.deref_mut(),
+      workspace_id,
+      &view_ids,
+      PUBLISH_VERSION_HISTORY_LEN,
+    )
+    .await?;
+    released_blob_s3_keys.extend(replaced_blob_s3_keys);
+    let unused_blob_s3_keys = select_unreferenced_published_blob_s3_keys(
+      txn.deref_mut(),
+      workspace_id,
+      &released_blob_s3_keys,
+    )
+    .await?;
+    insert_publish_audit_logs(
+      txn.deref_mut(),
+      workspace_id,
+      &view_ids,
+      PublishAuditAction::Publish,
+      publisher_uuid,
+    )
+    .await?;
+    txn.commit().await?;
+    Ok::<_, AppError>(Some(unused_blob_s3_keys))
+  }
+  .await;
+
+  match result {
+    Ok(Some(unused_blob_s3_keys)) => {
+      published_blob_storage
+        .delete_blobs(&unused_blob_s3_keys)
+        .await;
+      Ok(view_ids)
+    },
+    Ok(None) => {
+      let uploaded_blob_s3_keys = blob_s3_keys.into_iter().flatten().collect::<Vec<_>>();
+      published_blob_storage
+        .delete_blobs(&uploaded_blob_s3_keys)
+        .await;
+      // a concurrent request with the same idempotency key has published the collabs
+      get_recorded_publish_result(pg_pool, workspace_id, idempotency_key.unwrap_or_default()).await
+    },
+    Err(err) => {
+      let uploaded_blob_s3_keys = blob_s3_keys.into_iter().flatten().collect::<Vec<_>>();
+      published_blob_storage
+        .delete_blobs(&uploaded_blob_s3_keys)
+        .await;
+      Err(err)
+    },
+  }
+}
+
+/// Returns the result recorded for the idempotency key by another publish request.
+async fn get_recorded_publish_result(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  idempotency_key: &str,
+) -> Result<Vec<Uuid>, AppError> {
+  select_publish_idempotency_result(
+    pg_pool,
+    workspace_id,
+    idempotency_key,
+    PUBLISH_IDEMPOTENCY_KEY_EXPIRE_HOURS,
+  )
+  .await?
+  .ok_or_else(|| {
+    AppError::Internal(anyhow::anyhow!(
+      "The result of the publish request with idempotency key {} is missing",
+      idempotency_key
+    ))
+  })
 }
 
 pub async fn get_published_collab(
   pg_pool: &PgPool,
   publish_namespace: &str,
   publish_name: &str,
+  accept_language: Option<&str>,
 ) -> Result<serde_json::Value, AppError> {
-  let metadata = select_publish_collab_meta(pg_pool, publish_namespace, publish_name).await?;
+  let mut metadata = select_publish_collab_meta(pg_pool, publish_namespace, publish_name).await?;
+  if let Some(title) = accept_language.and_then(|lang| localized_publish_title(&metadata, lang)) {
+    if let Some(obj) = metadata.as_object_mut() {
+      obj.insert(
+        PUBLISH_METADATA_TITLE.to_string(),
+        serde_json::Value::String(title),
+      );
+    }
+  }
   Ok(metadata)
 }
 
+/// Checks that the user, or the guest when `uid` is None, can view the published views of the
+/// workspace that owns the publish namespace, according to its [PublishedViewAccess] setting.
+pub async fn check_published_view_access(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  uid: Option<i64>,
+) -> Result<(), AppError> {
+  let (workspace_id, settings) =
+    select_workspace_settings_for_publish_namespace(pg_pool, publish_namespace).await?;
+  check_workspace_published_view_access(pg_pool, &workspace_id, settings, uid).await
+}
+
+/// Same as [check_published_view_access], for the workspace that published the view.
+pub async fn check_published_view_access_for_view_id(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  uid: Option<i64>,
+) -> Result<(), AppError> {
+  let (workspace_id, settings) =
+    select_workspace_settings_for_published_view(pg_pool, view_id).await?;
+  check_workspace_published_view_access(pg_pool, &workspace_id, settings, uid).await
+}
+
+async fn check_workspace_published_view_access(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  settings: Option<AFWorkspaceSettings>,
+  uid: Option<i64>,
+) -> Result<(), AppError> {
+  let access = settings
+    .map(|settings| settings.published_view_access)
+    .unwrap_or_default();
+  match (access, uid) {
+    (PublishedViewAccess::Public, _) => Ok(()),
+    (_, None) => Err(AppError::NotLoggedIn(
+      "Published views of this workspace require login".to_string(),
+    )),
+    (PublishedViewAccess::LoggedIn, Some(_)) => Ok(()),
+    (PublishedViewAccess::WorkspaceMember, Some(uid)) => {
+      match select_workspace_member(pg_pool, &uid, workspace_id).await {
+        Ok(_) => Ok(()),
+        Err(AppError::RecordNotFound(_)) => Err(AppError::NotEnoughPermissions {
+          user: uid.to_string(),
+          action: format!("view the published views of workspace:{}", workspace_id),
+        }),
+        Err(err) => Err(err),
+      }
+    },
+  }
+}
+
+pub async fn get_namespace_published_views(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+) -> Result<Vec<PublishedViewSummary>, AppError> {
+  select_namespace_published_view_summaries(pg_pool, publish_namespace).await
+}
+
+pub async fn get_published_views_of_user(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+) -> Result<Vec<PublishedViewOfUser>, AppError> {
+  select_published_views_of_user(pg_pool, user_uuid).await
+}
+
 pub async fn get_published_collab_blob(
   pg_pool: &PgPool,
+  published_blob_storage: &PublishedBlobStorage,
   publish_namespace: &str,
   publish_name: &str,
 ) -> Result<Vec<u8>, AppError> {
-  select_published_collab_blob(pg_pool, publish_namespace, publish_name).await
+  let (blob, blob_s3_key) =
+    select_published_collab_blob(pg_pool, publish_namespace, publish_name).await?;
+  published_blob_storage.get_blob(blob, blob_s3_key).await
+}
+
+pub async fn get_published_collab_blob_etag(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<String, AppError> {
+  select_published_collab_blob_etag(pg_pool, publish_namespace, publish_name).await
+}
+
+pub async fn get_published_og_metadata(
+  pg_pool: &PgPool,
+  published_blob_storage: &PublishedBlobStorage,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<PublishOgMetadata, AppError> {
+  let (view_id, og_metadata) =
+    select_published_og_metadata(pg_pool, publish_namespace, publish_name).await?;
+  match og_metadata {
+    Some(og_metadata) => Ok(serde_json::from_value(og_metadata)?),
+    None => {
+      // Collabs published before the Open Graph metadata was introduced don't have it stored.
+      let metadata = select_publish_collab_meta(pg_pool, publish_namespace, publish_name).await?;
+      let blob = get_published_collab_blob(
+        pg_pool,
+        published_blob_storage,
+        publish_namespace,
+        publish_name,
+      )
+      .await?;
+      Ok(generate_publish_og_metadata(
+        &view_id.to_string(),
+        &metadata,
+        &blob,
+      ))
+    },
+  }
 }
 
 pub async fn get_published_collab_info(
   pg_pool: &PgPool,
   view_id: &Uuid,
+  accept_language: Option<&str>,
 ) -> Result<PublishInfo, AppError> {
-  select_published_collab_info(pg_pool, view_id).await
+  let mut info = select_published_collab_info(pg_pool, view_id).await?;
+  if let Some(accept_language) = accept_language {
+    let metadata = select_published_metadata_for_view_id(pg_pool, view_id).await?;
+    if let Some(title) = localized_publish_title(&metadata, accept_language) {
+      info.title = Some(title);
+    }
+  }
+  Ok(info)
+}
+
+/// Lists the databases of the workspace that have at least one published view, with the published
+/// views of each database. The views of a database are read from the workspace database collab.
+pub async fn list_workspace_published_databases(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  uid: &i64,
+  workspace_id: &Uuid,
+) -> Result<Vec<PublishedDatabase>, AppError> {
+  let mut published_infos: HashMap<String, PublishInfo> =
+    select_workspace_published_collab_infos(pg_pool, workspace_id)
+      .await?
+      .into_iter()
+      .map(|info| (info.view_id.to_string(), info))
+      .collect();
+  if published_infos.is_empty() {
+    return Ok(vec![]);
+  }
+
+  let database_storage_id = select_workspace(pg_pool, workspace_id)
+    .await?
+    .database_storage_id
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "workspace {} does not have a database storage",
+        workspace_id
+      ))
+    })?
+    .to_string();
+  let encoded_collab = collab_storage
+    .get_encode_collab(
+      uid,
+      QueryCollabParams::new(
+        &database_storage_id,
+        CollabType::WorkspaceDatabase,
+        workspace_id,
+      ),
+      false,
+    )
+    .await?;
+  let collab = Collab::new_with_source(
+    CollabOrigin::Server,
+    &database_storage_id,
+    DataSource::DocStateV1(encoded_collab.doc_state.to_vec()),
+    vec![],
+    false,
+  )
+  .map_err(|err| AppError::Internal(err.into()))?;
+  let json = collab.to_json_value();
+
+  let mut published_databases = vec![];
+  let databases = json
+    .get(WORKSPACE_DATABASES)
+    .and_then(Value::as_array)
+    .cloned()
+    .unwrap_or_default();
+  for database in databases {
+    let Some(database_id) = database.get("database_id").and_then(Value::as_str) else {
+      continue;
+    };
+    let published_views = database
+      .get("views")
+      .and_then(Value::as_array)
+      .into_iter()
+      .flatten()
+      .filter_map(Value::as_str)
+      .filter_map(|view_id| published_infos.remove(view_id))
+      .collect::<Vec<_>>();
+    if !published_views.is_empty() {
+      published_databases.push(PublishedDatabase {
+        database_id: database_id.to_string(),
+        published_views,
+      });
+    }
+  }
+  Ok(published_databases)
 }
 
+/// Unpublishes the views. Each view is processed independently: the views that are not
+/// published are reported as [UnpublishResult::NotFound], and the views that the user is not
+/// allowed to unpublish are reported as [UnpublishResult::Error]. Only the owner of the workspace
+/// and the publisher of a view can unpublish it.
 pub async fn delete_published_workspace_collab(
   pg_pool: &PgPool,
+  published_blob_storage: &PublishedBlobStorage,
   workspace_id: &Uuid,
   view_ids: &[Uuid],
   user_uuid: &Uuid,
+) -> Result<Vec<UnpublishViewResult>, AppError> {
+  let is_owner = select_user_is_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
+  let mut results = HashMap::with_capacity(view_ids.len());
+  let mut allowed_view_ids = vec![];
+  for (view_id, is_publisher) in
+    select_published_views_publisher(pg_pool, user_uuid, workspace_id, view_ids).await?
+  {
+    if is_owner || is_publisher {
+      allowed_view_ids.push(view_id);
+    } else {
+      results.insert(
+        view_id,
+        UnpublishResult::Error(
+          "User is not the owner of the workspace or the publisher of the view".to_string(),
+        ),
+      );
+    }
+  }
+
+  let mut blob_s3_keys = vec![];
+  if !allowed_view_ids.is_empty() {
+    let mut txn = pg_pool.begin().await?;
+    blob_s3_keys =
+      delete_publish_collab_versions(txn.deref_mut(), workspace_id, &allowed_view_ids).await?;
+    let deleted =
+      delete_published_collabs(txn.deref_mut(), workspace_id, &allowed_view_ids).await?;
+    let mut unpublished_view_ids = Vec::with_capacity(deleted.len());
+    for (view_id, blob_s3_key) in deleted {
+      unpublished_view_ids.push(view_id);
+      blob_s3_keys.extend(blob_s3_key);
+      results.insert(view_id, UnpublishResult::Unpublished);
+    }
+    insert_publish_audit_logs(
+      txn.deref_mut(),
+      workspace_id,
+      &unpublished_view_ids,
+      PublishAuditAction::Unpublish,
+      user_uuid,
+    )
+    .await?;
+    txn.commit().await?;
+  }
+  // The latest version of a view shares the blob of the published collab.
+  blob_s3_keys.sort();
+  blob_s3_keys.dedup();
+  published_blob_storage.delete_blobs(&blob_s3_keys).await;
+
+  Ok(
+    view_ids
+      .iter()
+      .map(|view_id| UnpublishViewResult {
+        view_id: *view_id,
+        result: results
+          .get(view_id)
+          .cloned()
+          .unwrap_or(UnpublishResult::NotFound),
+      })
+      .collect(),
+  )
+}
+
+/// Unpublishes all the published views of the workspace. Returns the ids of the unpublished views,
+/// which is empty if nothing was published.
+///
+/// Only the owner of the workspace can call this, so the `min_publish_role` setting never applies.
+/// The `publishing_enabled` setting is not checked either: it only stops new publishes, and the
+/// owner must still be able to take every view down after disabling publishing.
+pub async fn delete_all_published_workspace_collabs(
+  pg_pool: &PgPool,
+  published_blob_storage: &PublishedBlobStorage,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<Vec<Uuid>, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let version_blob_s3_keys =
+    delete_all_publish_collab_versions(txn.deref_mut(), workspace_id).await?;
+  let deleted = delete_all_published_collabs(txn.deref_mut(), workspace_id).await?;
+  let (view_ids, blob_s3_keys): (Vec<_>, Vec<_>) = deleted.into_iter().unzip();
+  insert_publish_audit_logs(
+    txn.deref_mut(),
+    workspace_id,
+    &view_ids,
+    PublishAuditAction::Unpublish,
+    user_uuid,
+  )
+  .await?;
+  txn.commit().await?;
+
+  let mut blob_s3_keys = blob_s3_keys
+    .into_iter()
+    .flatten()
+    .chain(version_blob_s3_keys)
+    .collect::<Vec<_>>();
+  // The latest version of a view shares the blob of the published collab.
+  blob_s3_keys.sort();
+  blob_s3_keys.dedup();
+  published_blob_storage.delete_blobs(&blob_s3_keys).await;
+  Ok(view_ids)
+}
+
+pub async fn list_publish_versions(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<PublishVersion>, AppError> {
+  select_publish_collab_versions(pg_pool, workspace_id, view_id).await
+}
+
+/// Restores the published view to one of its previous versions. The content of the version is
+/// published again under the current publish name, so the restore is itself recorded as the
+/// newest version of the view.
+/// `latest_version` is the latest version of the view known by the caller. The restore fails with
+/// [AppError::PublishVersionConflict] when the view was published again since.
+pub async fn restore_publish_version(
+  pg_pool: &PgPool,
+  published_blob_storage: &PublishedBlobStorage,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  version: i64,
+  latest_version: i64,
+  user_uuid: &Uuid,
+) -> Result<(), AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  let (publish_name, metadata, blob, blob_s3_key, metadata_version) =
+    select_publish_collab_version(pg_pool, workspace_id, view_id, version).await?;
+  let blob = published_blob_storage.get_blob(blob, blob_s3_key).await?;
+  // The versions don't keep the content hash, it is computed again so that publishing the same
+  // content afterwards is skipped as unchanged.
+  let content_hash = format!("{:x}", Sha256::digest(&blob));
+  let publish_item = PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id: *view_id,
+      publish_name,
+      metadata,
+      content_hash: Some(content_hash),
+      metadata_version: Some(metadata_version),
+    },
+    data: blob,
+  };
+  publish_collabs_if_latest(
+    pg_pool,
+    published_blob_storage,
+    workspace_id,
+    user_uuid,
+    vec![publish_item],
+    None,
+    Some(latest_version),
+  )
+  .await?;
+  Ok(())
+}
+
+/// Records a read of the published view in the background, so that serving the blob isn't slowed
+/// down by the stats.
+pub fn record_published_view_visit(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  publish_name: &str,
+  visitor_key: Option<String>,
+  uid: Option<i64>,
+) {
+  let pg_pool = pg_pool.clone();
+  let publish_namespace = publish_namespace.to_string();
+  let publish_name = publish_name.to_string();
+  tokio::spawn(async move {
+    if let Err(err) = insert_published_view_visit(
+      &pg_pool,
+      &publish_namespace,
+      &publish_name,
+      visitor_key.as_deref(),
+      uid,
+    )
+    .await
+    {
+      tracing::warn!(
+        "Failed to record the visit of published view {}/{}: {:?}",
+        publish_namespace,
+        publish_name,
+        err
+      );
+    }
+  });
+}
+
+pub async fn get_published_view_stats(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  exclude_owner: bool,
+) -> Result<PublishedViewStats, AppError> {
+  select_published_view_stats(pg_pool, workspace_id, view_id, exclude_owner).await
+}
+
+pub async fn get_publish_audit_log(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<PublishAuditLogEntry>, AppError> {
+  select_publish_audit_log(pg_pool, workspace_id, view_id).await
+}
+
+/// Switches the view backing the published collab with the given `publish_name` to
+/// `new_view_id`. The publish namespace and name, and therefore the public url, stay the same.
+pub async fn repoint_published_view(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  publish_name: &str,
+  new_view_id: &Uuid,
+  user_uuid: &Uuid,
 ) -> Result<(), AppError> {
-  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, view_ids).await?;
-  delete_published_collabs(pg_pool, workspace_id, view_ids).await?;
+  let old_view_id =
+    select_published_view_id_for_publish_name(pg_pool, workspace_id, publish_name).await?;
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[old_view_id]).await?;
+  if old_view_id == *new_view_id {
+    return Ok(());
+  }
+
+  if select_view_is_published(pg_pool, workspace_id, new_view_id).await? {
+    return Err(AppError::InvalidRequest(format!(
+      "View {} is already published",
+      new_view_id
+    )));
+  }
+
+  let new_collab_type =
+    select_collab_partition_key_in_workspace(pg_pool, workspace_id, &new_view_id.to_string())
+      .await?
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!(
+          "View {} does not exist in workspace {}",
+          new_view_id, workspace_id
+        ))
+      })?;
+  // The old view might have been deleted, in which case the collab type can't be compared.
+  let old_collab_type =
+    select_collab_partition_key_in_workspace(pg_pool, workspace_id, &old_view_id.to_string())
+      .await?;
+  if let Some(old_collab_type) = old_collab_type {
+    if old_collab_type != new_collab_type {
+      return Err(AppError::InvalidRequest(format!(
+        "View {} has a different collab type than the published view {}",
+        new_view_id, old_view_id
+      )));
+    }
+  }
+
+  let mut txn = pg_pool.begin().await?;
+  update_published_collab_view_id(txn.deref_mut(), workspace_id, publish_name, new_view_id).await?;
+  insert_publish_audit_logs(
+    txn.deref_mut(),
+    workspace_id,
+    &[old_view_id, *new_view_id],
+    PublishAuditAction::Repoint,
+    user_uuid,
+  )
+  .await?;
+  txn.commit().await?;
+  Ok(())
+}
+
+/// Changes the publish name of the published view. The view keeps being published, only its public
+/// url changes.
+pub async fn rename_published_view(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  new_publish_name: &str,
+  user_uuid: &Uuid,
+) -> Result<(), AppError> {
+  check_collab_publish_name(new_publish_name)?;
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  if let Some((_, other_view_id)) = select_published_view_using_publish_name(
+    pg_pool,
+    workspace_id,
+    &[new_publish_name.to_string()],
+    &[*view_id],
+  )
+  .await?
+  {
+    return Err(AppError::PublishNameAlreadyTaken(format!(
+      "publish name {} is already used by view {}",
+      new_publish_name, other_view_id
+    )));
+  }
+
+  let mut txn = pg_pool.begin().await?;
+  update_published_collab_publish_name(txn.deref_mut(), workspace_id, view_id, new_publish_name)
+    .await?;
+  insert_publish_audit_logs(
+    txn.deref_mut(),
+    workspace_id,
+    &[*view_id],
+    PublishAuditAction::Rename,
+    user_uuid,
+  )
+  .await?;
+  txn.commit().await?;
   Ok(())
 }
 
@@ -542,6 +1305,18 @@ pub async fn update_workspace_settings(
     setting.ai_model = ai_model;
   }
 
+  if let Some(published_view_access) = change.published_view_access {
+    setting.published_view_access = published_view_access;
+  }
+
+  if let Some(publishing_enabled) = change.publishing_enabled {
+    setting.publishing_enabled = publishing_enabled;
+  }
+
+  if let Some(min_publish_role) = change.min_publish_role {
+    setting.min_publish_role = min_publish_role;
+  }
+
   // Update the workspace settings in the database
   upsert_workspace_settings(&mut tx, workspace_id, &setting).await?;
   tx.commit().await?;
@@ -561,23 +1336,55 @@ async fn check_workspace_owner(
   }
 }
 
-async fn check_workspace_namespace(new_namespace: &str) -> Result<(), AppError> {
-  // Check len
-  if new_namespace.len() < 8 {
-    return Err(AppError::InvalidRequest(
-      "Namespace must be at least 8 characters long".to_string(),
+pub fn check_workspace_namespace(new_namespace: &str) -> Result<(), PublishNamespaceInvalidReason> {
+  PublishNamespaceRules::default().validate(new_namespace)
+}
+
+async fn check_workspace_publishing_enabled(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let settings = select_workspace_settings(pg_pool, workspace_id)
+    .await?
+    .unwrap_or_default();
+  if !settings.publishing_enabled {
+    return Err(AppError::PublishDisabled(
+      "Publishing is disabled for the workspace".to_string(),
     ));
   }
+  Ok(())
+}
 
-  // Only contain alphanumeric characters and hyphens
-  for c in new_namespace.chars() {
-    if !c.is_alphanumeric() && c != '-' {
-      return Err(AppError::InvalidRequest(
-        "Namespace must only contain alphanumeric characters and hyphens".to_string(),
-      ));
-    }
+/// Checks that the role of the user is at least the `min_publish_role` of the workspace settings.
+/// Only publishing is restricted: unpublishing is governed by the owner or publisher check, so a
+/// publisher can still take down their views after the policy is tightened.
+async fn check_workspace_min_publish_role(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<(), AppError> {
+  let min_publish_role = select_workspace_settings(pg_pool, workspace_id)
+    .await?
+    .unwrap_or_default()
+    .min_publish_role;
+  let uid = select_uid_from_uuid(pg_pool, user_uuid).await?;
+  let role = match select_user_role(pg_pool, &uid, workspace_id).await {
+    Ok(role) => role,
+    Err(AppError::RecordNotFound(_)) => {
+      return Err(AppError::NotEnoughPermissions {
+        user: uid.to_string(),
+        action: format!("publish in workspace:{}", workspace_id),
+      })
+    },
+    Err(err) => return Err(err),
+  };
+  // The roles are ordered from the most privileged to the least privileged
+  if role as i32 > min_publish_role.clone() as i32 {
+    return Err(AppError::UserUnAuthorized(format!(
+      "Only the {:?} role or above can publish in the workspace",
+      min_publish_role
+    )));
   }
-
   Ok(())
 }
 
@@ -620,3 +1427,98 @@ fn check_collab_publish_name(publish_name: &str) -> Result<(), AppError> {
 
   Ok(())
 }
+
+/// The content hash decides whether republishing an item is skipped, so it must be the lowercase
+/// hex encoded SHA-256 of the data.
+fn check_publish_content_hash(
+  publish_item: &PublishCollabItem<serde_json::Value, Vec<u8>>,
+) -> Result<(), AppError> {
+  if let Some(content_hash) = &publish_item.meta.content_hash {
+    if *content_hash != format!("{:x}", Sha256::digest(&publish_item.data)) {
+      return Err(AppError::InvalidRequest(format!(
+        "content hash of view {} must be the hex encoded SHA-256 of the data",
+        publish_item.meta.view_id
+      )));
+    }
+  }
+  Ok(())
+}
+
+/// A publish name can only be used by one view, including within the same batch.
+fn check_publish_names_in_batch(
+  publish_items: &[PublishCollabItem<serde_json::Value, Vec<u8>>],
+) -> Result<(), AppError> {
+  let mut view_id_by_publish_name = HashMap::new();
+  for item in publish_items {
+    if let Some(view_id) =
+      view_id_by_publish_name.insert(item.meta.publish_name.as_str(), item.meta.view_id)
+    {
+      if view_id != item.meta.view_id {
+        return Err(AppError::PublishNameAlreadyTaken(format!(
+          "publish name {} is already used by view {}",
+          item.meta.publish_name, view_id
+        )));
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Key of the default title in the publish metadata.
+const PUBLISH_METADATA_TITLE: &str = "title";
+/// Key of the localized titles in the publish metadata, e.g. `{"en": "Hello", "fr": "Bonjour"}`.
+const PUBLISH_METADATA_LOCALIZED_TITLES: &str = "localized_titles";
+
+/// Returns the entry of `localized_titles` that best matches the given `Accept-Language`
+/// header value. An exact language tag match is preferred over a primary subtag match,
+/// e.g. `fr-CA` falls back to `fr`. Returns None if nothing matches.
+fn localized_publish_title(metadata: &serde_json::Value, accept_language: &str) -> Option<String> {
+  let localized_titles = metadata
+    .get(PUBLISH_METADATA_LOCALIZED_TITLES)?
+    .as_object()?;
+  for lang in parse_accept_language(accept_language) {
+    let exact = localized_titles
+      .iter()
+      .find(|(key, _)| key.eq_ignore_ascii_case(&lang));
+    let primary = || {
+      let primary_subtag = lang.split('-').next().unwrap_or_default();
+      localized_titles.iter().find(|(key, _)| {
+        key
+          .split('-')
+          .next()
+          .unwrap_or_default()
+          .eq_ignore_ascii_case(primary_subtag)
+      })
+    };
+    if let Some(title) = exact.or_else(primary).and_then(|(_, title)| title.as_str()) {
+      return Some(title.to_string());
+    }
+  }
+  None
+}
+
+/// Parses an `Accept-Language` header value into language tags ordered by quality. Languages
+/// with `q=0` are not acceptable and are left out.
+fn parse_accept_language(accept_language: &str) -> Vec<String> {
+  let mut langs: Vec<(String, f32)> = accept_language
+    .split(',')
+    .filter_map(|part| {
+      let mut params = part.trim().split(';');
+      let lang = params.next()?.trim();
+      if lang.is_empty() || lang == "*" {
+        return None;
+      }
+      let quality = params
+        .find_map(|param| param.trim().strip_prefix("q="))
+        .and_then(|q| q.parse::<f32>().ok())
+        .unwrap_or(1.0);
+      if quality <= 0.0 {
+        return None;
+      }
+      Some((lang.to_string(), quality))
+    })
+    .collect();
+  // stable sort keeps the header order for languages with the same quality
+  langs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+  langs.into_iter().map(|(lang, _)| lang).collect()
+}
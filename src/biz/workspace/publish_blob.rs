@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use app_error::AppError;
+use database::file::s3_client_impl::S3BucketStorage;
+use database::file::BlobKey;
+use database_entity::dto::PublishCollabItem;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config::config::{PublishBlobStorage, PublishSetting};
+
+/// Stores the blobs of published collabs either in Postgres or in the S3 bucket, depending on
+/// the [PublishSetting]. The metadata of published collabs is always stored in Postgres, along
+/// with the object key of the blobs that are stored in the bucket.
+pub struct PublishedBlobStorage {
+  bucket_storage: Arc<S3BucketStorage>,
+  setting: PublishSetting,
+}
+
+impl PublishedBlobStorage {
+  pub fn new(bucket_storage: Arc<S3BucketStorage>, setting: PublishSetting) -> Self {
+    Self {
+      bucket_storage,
+      setting,
+    }
+  }
+
+  fn should_store_in_bucket(&self, blob_len: usize) -> bool {
+    self.setting.blob_storage == PublishBlobStorage::S3
+      && blob_len >= self.setting.s3_blob_threshold
+  }
+
+  /// Puts the blobs that should be stored in the bucket. Returns the object key of each blob, or
+  /// None if the blob should be stored in Postgres.
+  pub async fn put_blobs(
+    &self,
+    workspace_id: &Uuid,
+    publish_items: &[PublishCollabItem<serde_json::Value, Vec<u8>>],
+  ) -> Result<Vec<Option<String>>, AppError> {
+    let mut blob_s3_keys = Vec::with_capacity(publish_items.len());
+    for item in publish_items {
+      if !self.should_store_in_bucket(item.data.len()) {
+        blob_s3_keys.push(None);
+        continue;
+      }
+
+      let key = PublishedBlobKey {
+        workspace_id: *workspace_id,
+        blob_id: Uuid::new_v4().to_string(),
+      };
+      if let Err(err) = self
+        .bucket_storage
+        .put_blob_without_metadata(&key, &item.data)
+        .await
+      {
+        let uploaded_keys = blob_s3_keys.into_iter().flatten().collect::<Vec<_>>();
+        self.delete_blobs(&uploaded_keys).await;
+        return Err(err);
+      }
+      blob_s3_keys.push(Some(key.object_key()));
+    }
+    Ok(blob_s3_keys)
+  }
+
+  /// Returns the blob of a published collab. `blob` is the content of the blob column, which is
+  /// used when the blob is not stored in the bucket.
+  pub async fn get_blob(
+    &self,
+    blob: Vec<u8>,
+    blob_s3_key: Option<String>,
+  ) -> Result<Vec<u8>, AppError> {
+    match blob_s3_key {
+      Some(object_key) => {
+        self
+          .bucket_storage
+          .get_blob_by_object_key(&object_key)
+          .await
+      },
+      None => Ok(blob),
+    }
+  }
+
+  /// Deletes the blobs from the bucket. The published collabs that reference the blobs are
+  /// already updated at this point, so failures are only logged.
+  pub async fn delete_blobs(&self, blob_s3_keys: &[String]) {
+    for object_key in blob_s3_keys {
+      if let Err(err) = self
+        .bucket_storage
+        .delete_blob_without_metadata(object_key)
+        .await
+      {
+        warn!("Failed to delete published blob {}: {}", object_key, err);
+      }
+    }
+  }
+}
+
+struct PublishedBlobKey {
+  workspace_id: Uuid,
+  blob_id: String,
+}
+
+impl BlobKey for PublishedBlobKey {
+  fn workspace_id(&self) -> &Uuid {
+    &self.workspace_id
+  }
+
+  fn object_key(&self) -> String {
+    format!("{}/published/{}", self.workspace_id, self.blob_id)
+  }
+
+  fn meta_key(&self) -> String {
+    self.blob_id.clone()
+  }
+
+  fn e_tag(&self) -> &str {
+    &self.blob_id
+  }
+}
@@ -0,0 +1,144 @@
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab_document::blocks::{Block, DocumentData, TextDelta};
+use collab_document::document::Document;
+use database_entity::dto::PublishOgMetadata;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The maximum number of characters of the Open Graph description.
+const OG_DESCRIPTION_MAX_LEN: usize = 200;
+
+// Values of the view layout in the publish metadata
+const VIEW_LAYOUT_DOCUMENT: i64 = 0;
+const VIEW_LAYOUT_GRID: i64 = 1;
+const VIEW_LAYOUT_BOARD: i64 = 2;
+const VIEW_LAYOUT_CALENDAR: i64 = 3;
+
+/// Generates the Open Graph preview of a published view from its metadata and blob.
+///
+/// The title comes from `title` or `view.name` of the metadata, and the image from the cover
+/// stored in `view.extra`. For documents, the description is the first paragraph of the document.
+/// For databases, the description is the number of rows.
+pub fn generate_publish_og_metadata(
+  view_id: &str,
+  metadata: &Value,
+  blob: &[u8],
+) -> PublishOgMetadata {
+  let view = metadata.get("view");
+  let title = metadata
+    .get("title")
+    .or_else(|| view.and_then(|view| view.get("name")))
+    .and_then(Value::as_str)
+    .unwrap_or_default()
+    .to_string();
+
+  let description = match metadata
+    .get("description")
+    .and_then(Value::as_str)
+    .filter(|description| !description.is_empty())
+  {
+    Some(description) => Some(description.to_string()),
+    None => {
+      let layout = view
+        .and_then(|view| view.get("layout"))
+        .and_then(Value::as_i64)
+        .unwrap_or(VIEW_LAYOUT_DOCUMENT);
+      match layout {
+        VIEW_LAYOUT_GRID | VIEW_LAYOUT_BOARD | VIEW_LAYOUT_CALENDAR => {
+          database_row_count(blob).map(|count| format!("{} rows", count))
+        },
+        _ => document_first_paragraph(view_id, blob),
+      }
+    },
+  }
+  .map(|description| truncate(&description, OG_DESCRIPTION_MAX_LEN));
+
+  PublishOgMetadata {
+    title,
+    description,
+    image: view.and_then(cover_image),
+  }
+}
+
+/// The cover is stored as a json string in `view.extra`, for example:
+/// `{"cover":{"type":"custom","value":"https://..."}}`. Only covers that point to an url can be
+/// used as the preview image.
+fn cover_image(view: &Value) -> Option<String> {
+  let extra = view.get("extra")?.as_str()?;
+  let extra = serde_json::from_str::<Value>(extra).ok()?;
+  let cover = extra.get("cover")?.get("value")?.as_str()?;
+  if cover.starts_with("http://") || cover.starts_with("https://") {
+    Some(cover.to_string())
+  } else {
+    None
+  }
+}
+
+/// The blob of a published database is a json object that holds the encoded collab of each row
+/// in `database_row_collabs`.
+fn database_row_count(blob: &[u8]) -> Option<usize> {
+  let data = serde_json::from_slice::<Value>(blob).ok()?;
+  data
+    .get("database_row_collabs")?
+    .as_object()
+    .map(|rows| rows.len())
+}
+
+fn document_first_paragraph(view_id: &str, blob: &[u8]) -> Option<String> {
+  let document = Document::from_doc_state(
+    CollabOrigin::Empty,
+    DataSource::DocStateV1(blob.to_vec()),
+    view_id,
+    vec![],
+  )
+  .ok()?;
+  let data = document.get_document_data().ok()?;
+  first_paragraph(&data)
+}
+
+/// Returns the text of the first block of the document that is not empty.
+fn first_paragraph(document: &DocumentData) -> Option<String> {
+  let text_map = document.meta.text_map.as_ref();
+  let mut stack = vec![&document.page_id];
+  while let Some(block_id) = stack.pop() {
+    if let Some(block) = document.blocks.get(block_id) {
+      let text = block_text(block, text_map);
+      if !text.is_empty() {
+        return Some(text);
+      }
+      if let Some(children) = document.meta.children_map.get(&block.children) {
+        stack.extend(children.iter().rev());
+      }
+    }
+  }
+  None
+}
+
+fn block_text(block: &Block, text_map: Option<&HashMap<String, String>>) -> String {
+  let deltas = block
+    .data
+    .get("delta")
+    .and_then(|delta| serde_json::from_value::<Vec<TextDelta>>(delta.clone()).ok())
+    .or_else(|| {
+      let text_id = block.external_id.as_deref()?;
+      let json = text_map?.get(text_id)?;
+      serde_json::from_str::<Vec<TextDelta>>(json).ok()
+    })
+    .unwrap_or_default();
+
+  let mut text = String::new();
+  for delta in deltas {
+    if let TextDelta::Inserted(insert, _) = delta {
+      text.push_str(&insert);
+    }
+  }
+  text.trim().to_string()
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+  match s.char_indices().nth(max_chars) {
+    Some((idx, _)) => format!("{}…", &s[..idx]),
+    None => s.to_string(),
+  }
+}
@@ -19,6 +19,7 @@ pub struct Config {
   pub grpc_history: GrpcHistorySetting,
   pub collab: CollabSetting,
   pub mailer: MailerSetting,
+  pub publish: PublishSetting,
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
@@ -130,6 +131,36 @@ pub struct CollabSetting {
   pub edit_state_max_secs: i64,
 }
 
+#[derive(Clone, Debug)]
+pub struct PublishSetting {
+  pub blob_storage: PublishBlobStorage,
+  /// When the blob storage is [PublishBlobStorage::S3], blobs with at least this many bytes are
+  /// stored in the S3 bucket. Smaller blobs are stored in Postgres.
+  pub s3_blob_threshold: usize,
+}
+
+/// Where the blobs of published collabs are stored. The metadata always stays in Postgres.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublishBlobStorage {
+  Postgres,
+  S3,
+}
+
+impl FromStr for PublishBlobStorage {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "postgres" => Ok(Self::Postgres),
+      "s3" => Ok(Self::S3),
+      other => anyhow::bail!(
+        "{} is not a supported publish blob storage. Use either `postgres` or `s3`.",
+        other
+      ),
+    }
+  }
+}
+
 // Default values favor local development.
 pub fn get_configuration() -> Result<Config, anyhow::Error> {
   let config = Config {
@@ -200,6 +231,14 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       smtp_username: get_env_var("APPFLOWY_MAILER_SMTP_USERNAME", "sender@example.com"),
       smtp_password: get_env_var("APPFLOWY_MAILER_SMTP_PASSWORD", "password").into(),
     },
+    publish: PublishSetting {
+      blob_storage: get_env_var("APPFLOWY_PUBLISH_BLOB_STORAGE", "postgres")
+        .parse()
+        .context("fail to get APPFLOWY_PUBLISH_BLOB_STORAGE")?,
+      s3_blob_threshold: get_env_var("APPFLOWY_PUBLISH_S3_BLOB_THRESHOLD", "1048576")
+        .parse()
+        .context("fail to get APPFLOWY_PUBLISH_S3_BLOB_THRESHOLD")?,
+    },
   };
   Ok(config)
 }
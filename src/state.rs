@@ -27,6 +27,7 @@ use workspace_access::WorkspaceAccessControlImpl;
 
 use crate::api::metrics::RequestMetrics;
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::workspace::publish_blob::PublishedBlobStorage;
 use crate::config::config::Config;
 use crate::mailer::Mailer;
 
@@ -44,6 +45,7 @@ pub struct AppState {
   pub collab_access_control: CollabAccessControlImpl,
   pub workspace_access_control: WorkspaceAccessControlImpl,
   pub bucket_storage: Arc<S3BucketStorage>,
+  pub published_blob_storage: Arc<PublishedBlobStorage>,
   pub pg_listeners: Arc<PgListeners>,
   pub access_control: AccessControl,
   pub metrics: AppMetrics,
@@ -0,0 +1,65 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SyncControl, SyncObject};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_rt_entity::{AckCode, ClientCollabMessage, CollabAck, ServerCollabMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+#[tokio::test]
+async fn observer_stop_after_receive_collab_deleted_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let mut collab_deleted = sync_control.subscribe_collab_deleted();
+
+  // feed the tombstone that the server sends when the collab is deleted
+  let tombstone =
+    CollabAck::new(CollabOrigin::Server, object_id.clone(), 0, 0).with_code(AckCode::CollabDeleted);
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ClientAck(tombstone)))
+    .unwrap();
+
+  let deleted_object_id = timeout(Duration::from_secs(5), collab_deleted.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(deleted_object_id, object_id);
+
+  // the observer stops receiving messages, which drops the stream
+  timeout(Duration::from_secs(5), async {
+    while !stream_tx.is_closed() {
+      sleep(Duration::from_millis(100)).await;
+    }
+  })
+  .await
+  .unwrap();
+}
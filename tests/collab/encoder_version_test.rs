@@ -0,0 +1,237 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SyncControl, SyncObject, SyncReason};
+use client_api_test::{generate_unique_registered_user, TestClient};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::decoder::{Decode, DecoderV1};
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::{Collab, ReadTxn, StateVector, Update};
+use collab_entity::CollabType;
+use collab_rt_entity::{BroadcastSync, ClientCollabMessage, CollabAck, ServerCollabMessage};
+use collab_rt_protocol::{
+  CustomMessage, Message, MessageReader, SyncMessage, UpdateEncoderVersion,
+};
+use futures::channel::mpsc::{unbounded, SendError};
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+#[tokio::test]
+async fn negotiate_encoder_version_on_init_sync_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default().encoder_version(UpdateEncoderVersion::V2),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+
+  // the init sync ends with the encoder version asked by the client
+  assert!(sync_control
+    .init_sync(&collab.lock(), SyncReason::CollabInitialize)
+    .unwrap());
+  let init_sync = timeout(Duration::from_secs(5), sink_rx.next())
+    .await
+    .unwrap()
+    .unwrap()
+    .remove(0);
+  let mut decoder = DecoderV1::from(init_sync.payload().as_ref());
+  let messages = MessageReader::new(&mut decoder)
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  assert_eq!(
+    messages.last(),
+    Some(&Message::Custom(CustomMessage::EncoderVersion(
+      UpdateEncoderVersion::V2
+    )))
+  );
+
+  // the server agrees on v2
+  let ack = CollabAck::new(
+    CollabOrigin::Server,
+    object_id.clone(),
+    init_sync.msg_id(),
+    1,
+  )
+  .with_payload(
+    Message::Custom(CustomMessage::EncoderVersion(UpdateEncoderVersion::V2)).encode_v1(),
+  );
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ClientAck(ack)))
+    .unwrap();
+
+  // the broadcasts encoded with v2 start with the version
+  let remote_collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+  remote_collab.insert("key", "value");
+  let update = remote_collab
+    .transact()
+    .encode_state_as_update_v2(&StateVector::default());
+  let payload = [
+    Message::Custom(CustomMessage::EncoderVersion(UpdateEncoderVersion::V2)).encode_v1(),
+    Message::Sync(SyncMessage::Update(update)).encode_v1(),
+  ]
+  .concat();
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ServerBroadcast(
+      BroadcastSync::new(CollabOrigin::Server, object_id.clone(), payload, 1),
+    )))
+    .unwrap();
+
+  // the broadcasts without a version are still decoded with v1, for example when they were
+  // encoded before the negotiation
+  let sv = remote_collab.transact().state_vector();
+  remote_collab.insert("v1_key", "value");
+  let update = remote_collab.transact().encode_state_as_update_v1(&sv);
+  let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ServerBroadcast(
+      BroadcastSync::new(CollabOrigin::Server, object_id.clone(), payload, 2),
+    )))
+    .unwrap();
+
+  timeout(Duration::from_secs(5), async {
+    while collab.lock().to_json_value().get("v1_key").is_none() {
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+  assert_eq!(collab.lock().to_json_value()["key"], "value");
+}
+
+#[tokio::test]
+async fn init_sync_without_encoder_version_by_default_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (_stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+
+  // the servers that don't support the negotiation can parse the init sync
+  assert!(sync_control
+    .init_sync(&collab.lock(), SyncReason::CollabInitialize)
+    .unwrap());
+  let init_sync = timeout(Duration::from_secs(5), sink_rx.next())
+    .await
+    .unwrap()
+    .unwrap()
+    .remove(0);
+  let mut decoder = DecoderV1::from(init_sync.payload().as_ref());
+  let messages = MessageReader::new(&mut decoder)
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  assert!(messages
+    .iter()
+    .all(|msg| !matches!(msg, Message::Custom(_))));
+}
+
+#[tokio::test]
+async fn server_broadcasts_v2_updates_after_negotiation_test() {
+  let collab_type = CollabType::Unknown;
+  let registered_user = generate_unique_registered_user().await;
+  let mut client_1 = TestClient::user_with_new_device(registered_user.clone()).await;
+  let mut client_2 = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = client_1.workspace_id().await;
+  let object_id = client_1
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+
+  // receives the same messages from the server as the collab opened by client_2
+  let channel = client_2
+    .ws_client
+    .subscribe_collab(object_id.clone())
+    .unwrap();
+  let mut server_messages = channel.stream();
+  client_2
+    .open_collab(&workspace_id, &object_id, collab_type.clone())
+    .await;
+
+  client_1
+    .collabs
+    .get_mut(&object_id)
+    .unwrap()
+    .mutex_collab
+    .lock()
+    .insert("v2_key", "v2_value");
+
+  // client_2 asks for v2 during its init sync, so the update is broadcast to it with v2
+  let update = timeout(Duration::from_secs(10), async {
+    loop {
+      let msg = server_messages.next().await.unwrap().unwrap();
+      if !matches!(msg, ServerCollabMessage::ServerBroadcast(_)) {
+        continue;
+      }
+      let mut decoder = DecoderV1::from(msg.payload().as_ref());
+      let messages = MessageReader::new(&mut decoder)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+      if let [Message::Custom(CustomMessage::EncoderVersion(UpdateEncoderVersion::V2)), Message::Sync(SyncMessage::Update(update))] =
+        messages.as_slice()
+      {
+        return update.clone();
+      }
+    }
+  })
+  .await
+  .unwrap();
+  assert!(Update::decode_v2(&update).is_ok());
+
+  timeout(Duration::from_secs(10), async {
+    loop {
+      let json = client_2.collabs[&object_id]
+        .mutex_collab
+        .lock()
+        .to_json_value();
+      if json.get("v2_key").is_some() {
+        break;
+      }
+      sleep(Duration::from_millis(100)).await;
+    }
+  })
+  .await
+  .unwrap();
+}
@@ -0,0 +1,154 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SyncControl, SyncObject};
+use collab::core::collab::{DataSource, MutexCollab};
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::entity::EncodedCollab;
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_folder::Folder;
+use collab_rt_entity::{BroadcastSync, ClientCollabMessage, ServerCollabMessage};
+use collab_rt_protocol::{Message, SyncMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+use crate::collab::util::make_big_collab_doc_state;
+use crate::yrs_version::util::read_bytes_from_file;
+
+#[tokio::test]
+async fn malformed_folder_update_is_not_applied_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+  let before = collab.lock().to_json_value();
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Folder,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let metrics = sync_control.sync_metrics();
+
+  // the update doesn't contain the data required by a folder
+  let update = make_big_collab_doc_state(&object_id, "not_a_folder", "value".to_string());
+  let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+  let broadcast = ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+    CollabOrigin::Server,
+    object_id.clone(),
+    payload,
+    1,
+  ));
+  stream_tx.unbounded_send(Ok(broadcast)).unwrap();
+
+  timeout(Duration::from_secs(5), async {
+    while metrics.override_with_incorrect_data_count() != 1 {
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+
+  // the local folder is left untouched
+  assert_eq!(collab.lock().to_json_value(), before);
+}
+
+#[tokio::test]
+async fn folder_updates_are_validated_after_local_changes_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let data = read_bytes_from_file("folder_encode_collab_0172");
+  let doc_state = EncodedCollab::decode_from_bytes(&data)
+    .unwrap()
+    .doc_state
+    .to_vec();
+  let workspace_id = Folder::from_collab_doc_state(
+    322319512080748544,
+    CollabOrigin::Empty,
+    DataSource::DocStateV1(doc_state.clone()),
+    &object_id,
+    vec![],
+  )
+  .unwrap()
+  .get_workspace_id();
+  let collab = Arc::new(MutexCollab::new(
+    Collab::new_with_source(
+      origin.clone(),
+      &object_id,
+      DataSource::DocStateV1(doc_state),
+      vec![],
+      false,
+    )
+    .unwrap(),
+  ));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(&object_id, &workspace_id, CollabType::Folder, device_id),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let metrics = sync_control.sync_metrics();
+
+  let broadcast = |key: &str, seq_num: u32| {
+    let update = make_big_collab_doc_state(&object_id, key, "value".to_string());
+    let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+    ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+      CollabOrigin::Server,
+      object_id.clone(),
+      payload,
+      seq_num,
+    ))
+  };
+  let wait_for_key = |key: &'static str| {
+    let collab = collab.clone();
+    async move {
+      timeout(Duration::from_secs(5), async {
+        while collab.lock().to_json_value().get(key).is_none() {
+          sleep(Duration::from_millis(50)).await;
+        }
+      })
+      .await
+      .unwrap();
+    }
+  };
+
+  stream_tx.unbounded_send(Ok(broadcast("key_1", 1))).unwrap();
+  wait_for_key("key_1").await;
+
+  // the local change is not known by the snapshot the previous update was validated against
+  collab.lock().insert("local_key", "value");
+  stream_tx.unbounded_send(Ok(broadcast("key_2", 2))).unwrap();
+  wait_for_key("key_2").await;
+
+  let json = collab.lock().to_json_value();
+  assert!(json.get("local_key").is_some());
+  assert_eq!(metrics.override_with_incorrect_data_count(), 0);
+}
@@ -0,0 +1,157 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SyncControl, SyncObject};
+use client_api_test::{assert_server_collab, TestClient};
+use collab::core::collab::MutexCollab;
+use collab::core::collab_state::SyncState;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_rt_entity::{AckCode, ClientCollabMessage, CollabAck, ServerCollabMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+use tokio_stream::StreamExt;
+
+#[tokio::test]
+async fn force_resync_workspace_trigger_client_init_sync_test() {
+  let collab_type = CollabType::Unknown;
+  let mut test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let object_id = test_client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  test_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+
+  let mut sync_state = test_client
+    .collabs
+    .get(&object_id)
+    .unwrap()
+    .mutex_collab
+    .lock()
+    .subscribe_sync_state();
+
+  test_client
+    .api_client
+    .force_resync_workspace(&workspace_id)
+    .await
+    .unwrap();
+
+  // The force resync signal makes the client start a new init sync, which moves the sync state
+  // back to Syncing until the server replies.
+  let duration = Duration::from_secs(10);
+  let mut is_syncing = false;
+  while let Ok(Some(state)) = timeout(duration, sync_state.next()).await {
+    if state == SyncState::Syncing {
+      is_syncing = true;
+    } else if is_syncing && state == SyncState::SyncFinished {
+      break;
+    }
+  }
+  assert!(is_syncing, "client did not perform an init sync");
+
+  // After resync, the local edits keep syncing to the server.
+  test_client
+    .collabs
+    .get_mut(&object_id)
+    .unwrap()
+    .mutex_collab
+    .lock()
+    .insert("after_resync", "hello world");
+  test_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  assert_server_collab(
+    &workspace_id,
+    &mut test_client.api_client,
+    &object_id,
+    &collab_type,
+    10,
+    json!({
+      "after_resync": "hello world"
+    }),
+  )
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn force_resync_workspace_by_non_member_test() {
+  let owner = TestClient::new_user().await;
+  let other = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let result = other.api_client.force_resync_workspace(&workspace_id).await;
+  assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn force_resync_waits_for_collab_lock_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let mut force_resync = sync_control.subscribe_force_resync();
+
+  // the server asks for a resync while the collab is locked by a local edit
+  let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+  let (unlock_tx, unlock_rx) = std::sync::mpsc::channel::<()>();
+  let locked_collab = collab.clone();
+  let lock_thread = std::thread::spawn(move || {
+    let _lock_guard = locked_collab.lock();
+    locked_tx.send(()).unwrap();
+    let _ = unlock_rx.recv();
+  });
+  locked_rx.recv().unwrap();
+  let ack =
+    CollabAck::new(CollabOrigin::Server, object_id.clone(), 0, 0).with_code(AckCode::ForceResync);
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ClientAck(ack)))
+    .unwrap();
+  let resync_object_id = timeout(Duration::from_secs(5), force_resync.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(resync_object_id, object_id);
+  assert!(timeout(Duration::from_secs(1), sink_rx.next())
+    .await
+    .is_err());
+
+  // the init sync is sent once the lock is released
+  unlock_tx.send(()).unwrap();
+  lock_thread.join().unwrap();
+  let messages = timeout(Duration::from_secs(10), sink_rx.next())
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(messages
+    .iter()
+    .any(|msg| matches!(msg, ClientCollabMessage::ClientInitSync { .. })));
+}
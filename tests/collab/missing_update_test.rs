@@ -1,9 +1,21 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SyncControl, SyncObject};
 use client_api_test::{assert_client_collab_include_value, TestClient};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::Collab;
 use collab_entity::CollabType;
+use collab_rt_entity::{BroadcastSync, ClientCollabMessage, ServerCollabMessage};
+use collab_rt_protocol::{Message, SyncMessage};
 use database_entity::dto::AFAccessLevel;
+use futures::channel::mpsc::{unbounded, SendError};
+use futures::StreamExt;
 use serde_json::{json, Value};
-use std::time::Duration;
-use tokio::time::sleep;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::{sleep, timeout};
+
+use crate::collab::util::make_big_collab_doc_state;
 
 #[tokio::test]
 async fn client_apply_update_find_missing_update_test() {
@@ -36,6 +48,61 @@ async fn client_ping_find_missing_update_test() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn pull_missing_updates_after_configured_delay_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let _sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::new().pull_missing_updates_delay(Duration::from_millis(100)),
+    Arc::downgrade(&collab),
+    None,
+  );
+
+  let broadcast = |key: &str, seq_num: u32| {
+    let update = make_big_collab_doc_state(&object_id, key, "value".to_string());
+    let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+    ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+      CollabOrigin::Server,
+      object_id.clone(),
+      payload,
+      seq_num,
+    ))
+  };
+
+  // the broadcast with seq_num 2 is missing, so the client pulls the missing updates
+  let start = Instant::now();
+  stream_tx.unbounded_send(Ok(broadcast("key_1", 1))).unwrap();
+  stream_tx.unbounded_send(Ok(broadcast("key_3", 3))).unwrap();
+  timeout(Duration::from_secs(5), sink_rx.next())
+    .await
+    .unwrap()
+    .unwrap();
+
+  // the default delay is 3 seconds
+  assert!(start.elapsed() < Duration::from_secs(3));
+}
+
 /// Create two clients and the first client makes an edit to the collaborative document.
 /// The second client did do init sync but disable receive message, so it will miss the first edit.
 async fn make_clients() -> (TestClient, TestClient, String, Value) {
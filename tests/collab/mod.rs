@@ -1,9 +1,19 @@
 mod awareness_test;
 mod collab_curd_test;
+mod collab_deleted_test;
+mod encoder_version_test;
+mod folder_validation_test;
+mod force_resync_test;
 mod member_crud;
 mod missing_update_test;
 mod multi_devices_edit;
 mod permission_test;
+mod remote_sync_state_test;
+mod seq_status_test;
 mod single_device_edit;
 mod storage_test;
+mod stream_reconnect_test;
+mod sync_metrics_test;
+mod sync_progress_test;
+mod sync_watermark_test;
 pub mod util;
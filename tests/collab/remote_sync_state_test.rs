@@ -0,0 +1,117 @@
+use client_api::collab_sync::{
+  RemoteSyncState, RemoteSyncStatus, SinkConfig, StreamConfig, SyncControl, SyncObject, SyncReason,
+};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_rt_entity::{BroadcastSync, ClientCollabMessage, CollabAck, ServerCollabMessage};
+use collab_rt_protocol::{Message, SyncMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::timeout;
+
+use crate::collab::util::make_big_collab_doc_state;
+
+#[tokio::test]
+async fn remote_sync_state_transitions_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::new().pull_missing_updates_delay(Duration::from_secs(60)),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let mut remote_sync_state = sync_control.subscribe_remote_sync_state();
+  assert_eq!(
+    *remote_sync_state.borrow(),
+    RemoteSyncState {
+      object_id: object_id.clone(),
+      status: RemoteSyncStatus::InitSync,
+    }
+  );
+
+  // the collab is synced once the server replies to the init sync
+  assert!(sync_control
+    .init_sync(&collab.lock(), SyncReason::CollabInitialize)
+    .unwrap());
+  let init_sync = timeout(Duration::from_secs(5), sink_rx.next())
+    .await
+    .unwrap()
+    .unwrap()
+    .remove(0);
+  let ack = CollabAck::new(
+    CollabOrigin::Server,
+    object_id.clone(),
+    init_sync.msg_id(),
+    1,
+  );
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ClientAck(ack)))
+    .unwrap();
+  wait_for_status(&mut remote_sync_state, RemoteSyncStatus::Synced).await;
+
+  // the broadcast with seq_num 2 is missing
+  let broadcast = |key: &str, seq_num: u32| {
+    let update = make_big_collab_doc_state(&object_id, key, "value".to_string());
+    let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+    ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+      CollabOrigin::Server,
+      object_id.clone(),
+      payload,
+      seq_num,
+    ))
+  };
+  stream_tx.unbounded_send(Ok(broadcast("key_1", 1))).unwrap();
+  stream_tx.unbounded_send(Ok(broadcast("key_3", 3))).unwrap();
+  wait_for_status(
+    &mut remote_sync_state,
+    RemoteSyncStatus::PullingMissingUpdates,
+  )
+  .await;
+
+  // a transient error of the stream
+  let (tx, rx) = unbounded::<()>();
+  drop(rx);
+  let send_error = tx.unbounded_send(()).unwrap_err().into_send_error();
+  stream_tx.unbounded_send(Err(send_error)).unwrap();
+  wait_for_status(&mut remote_sync_state, RemoteSyncStatus::Errored).await;
+}
+
+async fn wait_for_status(
+  remote_sync_state: &mut watch::Receiver<RemoteSyncState>,
+  status: RemoteSyncStatus,
+) {
+  timeout(
+    Duration::from_secs(5),
+    remote_sync_state.wait_for(|state| state.status == status),
+  )
+  .await
+  .unwrap()
+  .unwrap();
+}
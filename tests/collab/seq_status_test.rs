@@ -0,0 +1,104 @@
+use client_api::collab_sync::{
+  SeqNumSnapshot, SinkConfig, StreamConfig, SyncControl, SyncObject, SyncReason,
+};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_rt_entity::{BroadcastSync, ClientCollabMessage, CollabAck, ServerCollabMessage};
+use collab_rt_protocol::{Message, SyncMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+use crate::collab::util::make_big_collab_doc_state;
+
+#[tokio::test]
+async fn seq_status_after_sync_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+  assert_eq!(
+    sync_control.seq_status(),
+    SeqNumSnapshot {
+      broadcast_seq: 0,
+      ack_seq: 0,
+      miss_update_count: 0,
+    }
+  );
+
+  assert!(sync_control
+    .init_sync(&collab.lock(), SyncReason::CollabInitialize)
+    .unwrap());
+  let init_sync = timeout(Duration::from_secs(5), sink_rx.next())
+    .await
+    .unwrap()
+    .unwrap()
+    .remove(0);
+  let ack = CollabAck::new(
+    CollabOrigin::Server,
+    object_id.clone(),
+    init_sync.msg_id(),
+    3,
+  );
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ClientAck(ack)))
+    .unwrap();
+
+  // the client is in sync with the server once the init sync is acked
+  timeout(Duration::from_secs(5), async {
+    while sync_control.seq_status().ack_seq != 3 {
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+  let status = sync_control.seq_status();
+  assert_eq!(status.ack_seq, status.broadcast_seq);
+  assert_eq!(status.miss_update_count, 0);
+
+  // the broadcast that follows the ack is contiguous
+  let update = make_big_collab_doc_state(&object_id, "key", "value".to_string());
+  let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ServerBroadcast(
+      BroadcastSync::new(CollabOrigin::Server, object_id.clone(), payload, 4),
+    )))
+    .unwrap();
+  timeout(Duration::from_secs(5), async {
+    while sync_control.seq_status().broadcast_seq != 4 {
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+  assert_eq!(sync_control.seq_status().miss_update_count, 0);
+}
@@ -0,0 +1,208 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SubscribeStream, SyncControl, SyncObject};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_rt_entity::{BroadcastSync, ClientCollabMessage, ServerCollabMessage};
+use collab_rt_protocol::{Message, SyncMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+use crate::collab::util::make_big_collab_doc_state;
+
+#[tokio::test]
+async fn observer_keep_receiving_after_stream_error_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let _sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::new().reconnect_delay(Duration::from_millis(50)),
+    Arc::downgrade(&collab),
+    None,
+  );
+
+  let broadcast = |key: &str, seq_num: u32| {
+    let update = make_big_collab_doc_state(&object_id, key, "value".to_string());
+    let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+    ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+      CollabOrigin::Server,
+      object_id.clone(),
+      payload,
+      seq_num,
+    ))
+  };
+
+  stream_tx.unbounded_send(Ok(broadcast("key_1", 1))).unwrap();
+  // a transient error of the stream, for example when the connection is lost
+  stream_tx.unbounded_send(Err(send_error())).unwrap();
+  stream_tx.unbounded_send(Ok(broadcast("key_2", 2))).unwrap();
+
+  timeout(Duration::from_secs(5), async {
+    loop {
+      let json = collab.lock().to_json_value();
+      if json.get("key_1").is_some() && json.get("key_2").is_some() {
+        break;
+      }
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+  assert!(!stream_tx.is_closed());
+}
+
+#[tokio::test]
+async fn observer_subscribe_again_after_stream_error_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let (new_stream_tx, new_stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let new_streams = Arc::new(Mutex::new(VecDeque::from([new_stream])));
+  let subscribe_stream: SubscribeStream<_> = {
+    let new_streams = new_streams.clone();
+    Box::new(move || {
+      new_streams
+        .lock()
+        .unwrap()
+        .pop_front()
+        .unwrap_or_else(|| unbounded().1)
+    })
+  };
+  let _sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    Some(subscribe_stream),
+    StreamConfig::new().reconnect_delay(Duration::from_millis(50)),
+    Arc::downgrade(&collab),
+    None,
+  );
+
+  let broadcast = |key: &str, seq_num: u32| {
+    let update = make_big_collab_doc_state(&object_id, key, "value".to_string());
+    let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+    ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+      CollabOrigin::Server,
+      object_id.clone(),
+      payload,
+      seq_num,
+    ))
+  };
+
+  stream_tx.unbounded_send(Ok(broadcast("key_1", 1))).unwrap();
+  // a transient error of the stream, for example when the connection is lost
+  stream_tx.unbounded_send(Err(send_error())).unwrap();
+  // the next messages are only received by the new subscription
+  new_stream_tx
+    .unbounded_send(Ok(broadcast("key_2", 2)))
+    .unwrap();
+
+  timeout(Duration::from_secs(5), async {
+    loop {
+      let json = collab.lock().to_json_value();
+      if json.get("key_1").is_some() && json.get("key_2").is_some() {
+        break;
+      }
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+  // the stream that yielded the error is dropped in favor of the new subscription
+  assert!(stream_tx.is_closed());
+  assert!(!new_stream_tx.is_closed());
+  assert!(new_streams.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn observer_stop_after_max_reconnect_attempts_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let _sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::new()
+      .reconnect_delay(Duration::from_millis(10))
+      .max_reconnect_attempts(2),
+    Arc::downgrade(&collab),
+    None,
+  );
+
+  for _ in 0..3 {
+    stream_tx.unbounded_send(Err(send_error())).unwrap();
+  }
+
+  // the observer stops receiving messages, which drops the stream
+  timeout(Duration::from_secs(5), async {
+    while !stream_tx.is_closed() {
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+}
+
+fn send_error() -> SendError {
+  let (tx, rx) = unbounded::<()>();
+  drop(rx);
+  tx.unbounded_send(()).unwrap_err().into_send_error()
+}
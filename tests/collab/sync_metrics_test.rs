@@ -0,0 +1,81 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SyncControl, SyncObject};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_rt_entity::{
+  AckCode, BroadcastSync, ClientCollabMessage, CollabAck, ServerCollabMessage,
+};
+use collab_rt_protocol::{Message, SyncMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+use crate::collab::util::make_big_collab_doc_state;
+
+#[tokio::test]
+async fn sync_metrics_count_resync_errors_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let metrics = sync_control.sync_metrics();
+  assert_eq!(metrics.miss_updates_count(), 0);
+  assert_eq!(metrics.cannot_apply_update_count(), 0);
+
+  // the broadcast with seq_num 2 is missing
+  let broadcast = |key: &str, seq_num: u32| {
+    let update = make_big_collab_doc_state(&object_id, key, "value".to_string());
+    let payload = Message::Sync(SyncMessage::Update(update)).encode_v1();
+    ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+      CollabOrigin::Server,
+      object_id.clone(),
+      payload,
+      seq_num,
+    ))
+  };
+  stream_tx.unbounded_send(Ok(broadcast("key_1", 1))).unwrap();
+  stream_tx.unbounded_send(Ok(broadcast("key_3", 3))).unwrap();
+
+  // the server can't apply the updates of the client
+  let ack = CollabAck::new(CollabOrigin::Server, object_id.clone(), 0, 0)
+    .with_code(AckCode::CannotApplyUpdate);
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ClientAck(ack)))
+    .unwrap();
+
+  timeout(Duration::from_secs(5), async {
+    while metrics.miss_updates_count() != 1 || metrics.cannot_apply_update_count() != 1 {
+      sleep(Duration::from_millis(50)).await;
+    }
+  })
+  .await
+  .unwrap();
+  assert_eq!(metrics.override_with_incorrect_data_count(), 0);
+}
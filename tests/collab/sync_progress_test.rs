@@ -0,0 +1,135 @@
+use client_api::collab_sync::{SinkConfig, StreamConfig, SyncControl, SyncObject};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::encoder::Encode;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use collab_rt_entity::{BroadcastSync, ClientCollabMessage, ServerCollabMessage};
+use collab_rt_protocol::{Message, SyncMessage};
+use futures::channel::mpsc::{unbounded, SendError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+use crate::collab::util::{generate_random_string, make_big_collab_doc_state};
+
+#[tokio::test]
+async fn sync_progress_emitted_for_each_applied_sync_step_2_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default(),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let mut sync_progress = sync_control.subscribe_sync_progress();
+
+  // a large init sync that is split into multiple SyncStep2 chunks
+  let chunk_count = 5;
+  let mut payload = vec![];
+  let mut update_bytes = 0;
+  for i in 0..chunk_count {
+    let update = make_big_collab_doc_state(
+      &object_id,
+      &format!("key_{}", i),
+      generate_random_string(10_000),
+    );
+    update_bytes += update.len();
+    payload.extend(Message::Sync(SyncMessage::SyncStep2(update)).encode_v1());
+  }
+  let payload_len = payload.len();
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ServerBroadcast(
+      BroadcastSync::new(CollabOrigin::Server, object_id.clone(), payload, 1),
+    )))
+    .unwrap();
+
+  for i in 1..=chunk_count {
+    let progress = timeout(Duration::from_secs(5), sync_progress.recv())
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(progress.object_id, object_id);
+    assert_eq!(progress.applied_updates, i);
+    assert_eq!(progress.total_bytes, payload_len);
+    if i == chunk_count {
+      assert_eq!(progress.applied_bytes, update_bytes);
+    }
+  }
+
+  let json = collab.lock().to_json_value();
+  for i in 0..chunk_count {
+    assert!(json.get(format!("key_{}", i)).is_some());
+  }
+}
+
+#[tokio::test]
+async fn apply_remote_updates_inline_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+
+  let (sink, _sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = SyncControl::new(
+    SyncObject::new(
+      &object_id,
+      "fake_workspace_id",
+      CollabType::Unknown,
+      device_id,
+    ),
+    origin,
+    sink,
+    SinkConfig::default(),
+    stream,
+    None,
+    StreamConfig::default().catch_apply_panic(false),
+    Arc::downgrade(&collab),
+    None,
+  );
+  let mut sync_progress = sync_control.subscribe_sync_progress();
+
+  let update = make_big_collab_doc_state(&object_id, "key", "value".to_string());
+  let payload = Message::Sync(SyncMessage::SyncStep2(update)).encode_v1();
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ServerBroadcast(
+      BroadcastSync::new(CollabOrigin::Server, object_id.clone(), payload, 1),
+    )))
+    .unwrap();
+
+  let progress = timeout(Duration::from_secs(5), sync_progress.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(progress.applied_updates, 1);
+
+  let json = collab.lock().to_json_value();
+  assert_eq!(json["key"], "value");
+}
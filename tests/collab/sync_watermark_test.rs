@@ -0,0 +1,145 @@
+use client_api::collab_sync::{
+  SinkConfig, StreamConfig, SyncControl, SyncObject, SyncReason, SyncWatermark, SyncWatermarkStore,
+};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::decoder::{Decode, DecoderV1};
+use collab::preclude::{Collab, Update};
+use collab_entity::CollabType;
+use collab_rt_entity::{ClientCollabMessage, CollabAck, ServerCollabMessage};
+use collab_rt_protocol::{Message, MessageReader, SyncMessage};
+use futures::channel::mpsc::{unbounded, SendError, UnboundedReceiver};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+#[derive(Default)]
+struct MemoryWatermarkStore {
+  watermarks: Mutex<HashMap<String, SyncWatermark>>,
+}
+
+impl SyncWatermarkStore for MemoryWatermarkStore {
+  fn load(&self, object_id: &str) -> Option<SyncWatermark> {
+    self.watermarks.lock().unwrap().get(object_id).cloned()
+  }
+
+  fn store(&self, watermark: SyncWatermark) {
+    self
+      .watermarks
+      .lock()
+      .unwrap()
+      .insert(watermark.object_id.clone(), watermark);
+  }
+}
+
+#[tokio::test]
+async fn sync_from_stored_watermark_after_restart_test() {
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let device_id = "fake_device_id";
+  let origin = CollabOrigin::Client(CollabClient::new(1, device_id.to_string()));
+  let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+    origin.clone(),
+    &object_id,
+    vec![],
+    false,
+  )));
+  collab.lock().insert("synced_key", "synced_value");
+  let store = Arc::new(MemoryWatermarkStore::default());
+  let new_sync_control = |sink, stream| {
+    SyncControl::new(
+      SyncObject::new(
+        &object_id,
+        "fake_workspace_id",
+        CollabType::Unknown,
+        device_id,
+      ),
+      origin.clone(),
+      sink,
+      SinkConfig::default(),
+      stream,
+      None,
+      StreamConfig::default(),
+      Arc::downgrade(&collab),
+      Some(store.clone() as Arc<dyn SyncWatermarkStore>),
+    )
+  };
+
+  // without a watermark, the full init sync is performed
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = new_sync_control(sink, stream);
+  assert!(sync_control
+    .init_sync(&collab.lock(), SyncReason::CollabInitialize)
+    .unwrap());
+  let init_sync = next_message(&mut sink_rx).await;
+  assert!(init_sync.is_init_sync());
+
+  // the watermark is stored once the server acks the init sync
+  let ack = CollabAck::new(
+    CollabOrigin::Server,
+    object_id.clone(),
+    init_sync.msg_id(),
+    1,
+  );
+  stream_tx
+    .unbounded_send(Ok(ServerCollabMessage::ClientAck(ack)))
+    .unwrap();
+  let watermark = timeout(Duration::from_secs(5), async {
+    loop {
+      if let Some(watermark) = store.load(&object_id) {
+        return watermark;
+      }
+      sleep(Duration::from_millis(100)).await;
+    }
+  })
+  .await
+  .unwrap();
+  assert_eq!(watermark.ack_seq, 1);
+
+  // edit while the collab is not syncing, then recreate the sync control as after a restart
+  drop(sync_control);
+  collab.lock().insert("offline_key", "offline_value");
+  let (sink, mut sink_rx) = unbounded::<Vec<ClientCollabMessage>>();
+  let (_stream_tx, stream) = unbounded::<Result<ServerCollabMessage, SendError>>();
+  let sync_control = new_sync_control(sink, stream);
+  assert!(sync_control
+    .init_sync(&collab.lock(), SyncReason::CollabInitialize)
+    .unwrap());
+
+  // a diff sync is sent instead of the init sync, which only carries the offline edit
+  let diff_sync = next_message(&mut sink_rx).await;
+  assert!(matches!(
+    diff_sync,
+    ClientCollabMessage::ClientUpdateSync { .. }
+  ));
+  let mut decoder = DecoderV1::from(diff_sync.payload().as_ref());
+  let messages = MessageReader::new(&mut decoder)
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  assert!(matches!(
+    messages[0],
+    Message::Sync(SyncMessage::SyncStep1(_))
+  ));
+  let Message::Sync(SyncMessage::Update(update)) = &messages[1] else {
+    panic!("expected the updates since the watermark");
+  };
+  let remote_collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+  remote_collab.with_origin_transact_mut(|txn| {
+    txn.apply_update(Update::decode_v1(update).unwrap());
+  });
+  let json = remote_collab.to_json_value();
+  assert_eq!(json["offline_key"], "offline_value");
+  assert!(json.get("synced_key").is_none());
+}
+
+async fn next_message(
+  sink_rx: &mut UnboundedReceiver<Vec<ClientCollabMessage>>,
+) -> ClientCollabMessage {
+  timeout(Duration::from_secs(5), sink_rx.next())
+    .await
+    .unwrap()
+    .unwrap()
+    .remove(0)
+}
@@ -1,4 +1,5 @@
 use app_error::ErrorCode;
+use client_api::{Client, ClientConfiguration};
 use client_api_test::*;
 
 #[tokio::test]
@@ -7,7 +8,9 @@ async fn sign_in_unknown_user() {
   let password = "Hello123!";
   let c = localhost_client();
   let err = c.sign_in_password(&email, password).await.unwrap_err();
-  assert_eq!(err.code, ErrorCode::OAuthError);
+  // gotrue does not reveal whether the email is registered, so an unknown user is reported the
+  // same way as a wrong password
+  assert_eq!(err.code, ErrorCode::InvalidCredentials);
   assert!(!err.message.is_empty());
 }
 
@@ -24,7 +27,7 @@ async fn sign_in_wrong_password() {
     .sign_in_password(&email, wrong_password)
     .await
     .unwrap_err();
-  assert_eq!(err.code, ErrorCode::OAuthError);
+  assert_eq!(err.code, ErrorCode::InvalidCredentials);
   assert!(!err.message.is_empty());
 }
 
@@ -37,10 +40,24 @@ async fn sign_in_unconfirmed_email() {
   c.sign_up(&email, password).await.unwrap();
 
   let err = c.sign_in_password(&email, password).await.unwrap_err();
-  assert_eq!(err.code, ErrorCode::OAuthError);
+  assert_eq!(err.code, ErrorCode::EmailNotConfirmed);
   assert!(!err.message.is_empty());
 }
 
+#[tokio::test]
+async fn resend_confirmation_email_to_unconfirmed_email() {
+  let c = localhost_client();
+
+  let email = generate_unique_email();
+  let password = "Hello123!";
+  c.sign_up(&email, password).await.unwrap();
+
+  let err = c.sign_in_password(&email, password).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::EmailNotConfirmed);
+
+  c.resend_confirmation_email(&email).await.unwrap();
+}
+
 #[tokio::test]
 async fn sign_in_success() {
   let registered_user = generate_unique_registered_user().await;
@@ -109,3 +126,21 @@ async fn sign_in_with_magic_link() {
   let resp = c.sign_in_with_magic_link(&email, None).await;
   assert!(resp.is_ok());
 }
+
+#[tokio::test]
+async fn sign_in_with_unreachable_gotrue() {
+  // nothing listens on port 1, so connecting to gotrue fails
+  let c = Client::new(
+    &LOCALHOST_URL,
+    &LOCALHOST_WS,
+    "http://localhost:1",
+    &uuid::Uuid::new_v4().to_string(),
+    ClientConfiguration::default(),
+    "0.5.0",
+  );
+  let err = c
+    .sign_in_password(&generate_unique_email(), "Hello123!")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::ServiceUnavailable);
+}
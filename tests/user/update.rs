@@ -1,6 +1,7 @@
 use app_error::ErrorCode;
 use client_api::ws::{WSClient, WSClientConfig};
 use client_api_test::*;
+use database_entity::dto::USER_METADATA_ICON_URL;
 use serde_json::json;
 use shared_entity::dto::auth_dto::{UpdateUserParams, UserMetaData};
 use std::time::Duration;
@@ -185,3 +186,24 @@ async fn user_change_notify_test() {
     },
   }
 }
+
+#[tokio::test]
+async fn update_user_icon_url() {
+  let (c, user) = generate_unique_registered_user_client().await;
+  c.sign_in_password(&user.email, &user.password)
+    .await
+    .unwrap();
+
+  let profile = c.get_profile().await.unwrap();
+  assert_eq!(profile.icon_url(), None);
+
+  let icon_url = "https://appflowy.io/avatar.png";
+  let mut metadata = UserMetaData::new();
+  metadata.insert(USER_METADATA_ICON_URL, icon_url);
+  c.update_user(UpdateUserParams::new().with_metadata(metadata))
+    .await
+    .unwrap();
+
+  let profile = c.get_profile().await.unwrap();
+  assert_eq!(profile.icon_url().as_deref(), Some(icon_url));
+}
@@ -1,5 +1,28 @@
+use app_error::ErrorCode;
+use appflowy_cloud::api::workspace::PUBLISH_STREAM_BATCH_MAX_ITEMS;
 use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
-use client_api_test::{generate_unique_registered_user_client, localhost_client};
+use client_api::PublishCollabItemStream;
+use client_api_test::{generate_unique_registered_user_client, localhost_client, TestClient};
+use collab::core::collab::MutexCollab;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::{Any, Collab, MapPrelim};
+use collab_document::document::Document;
+use collab_entity::define::WORKSPACE_DATABASES;
+use collab_entity::CollabType;
+use database_entity::dto::{
+  AFRole, AFWorkspaceSettingsChange, CreateCollabParams, PublishAuditAction,
+  PublishNamespaceInvalidReason, PublishNamespaceRules, PublishedViewAccess, UnpublishResult,
+};
+use futures::StreamExt;
+use reqwest::Method;
+use sha2::{Digest, Sha256};
+use shared_entity::dto::workspace_dto::CreateWorkspaceParam;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use workspace_template::document::get_started::get_started_document_data;
+
+use crate::collab::util::test_encode_collab_v1;
 
 #[tokio::test]
 async fn test_set_publish_namespace_set() {
@@ -64,6 +87,64 @@ async fn test_set_publish_namespace_set() {
   }
 }
 
+#[tokio::test]
+async fn test_set_publish_namespace_invalid_reason() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+
+  let long_namespace = "a".repeat(51);
+  for (namespace, expected_reason) in [
+    ("a", PublishNamespaceInvalidReason::TooShort),
+    (
+      long_namespace.as_str(),
+      PublishNamespaceInvalidReason::TooLong,
+    ),
+    (
+      "/|(*&)(&#@!",
+      PublishNamespaceInvalidReason::InvalidCharacters,
+    ),
+    ("Settings", PublishNamespaceInvalidReason::Reserved),
+    ("templates", PublishNamespaceInvalidReason::Reserved),
+  ] {
+    let err = c
+      .set_workspace_publish_namespace(&workspace_id, namespace)
+      .await
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRequest);
+    assert_eq!(err.invalid_reason, Some(expected_reason));
+  }
+}
+
+#[tokio::test]
+async fn test_publish_namespace_rules() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+
+  let rules = localhost_client()
+    .get_publish_namespace_rules()
+    .await
+    .unwrap();
+  assert_eq!(rules, PublishNamespaceRules::default());
+
+  // the rules reject the same namespaces as the server
+  let too_long = "a".repeat(rules.max_len + 1);
+  for namespace in ["a", too_long.as_str(), "/|(*&)(&#@!", "settings"] {
+    assert!(rules.validate(namespace).is_err());
+    let err = c
+      .set_workspace_publish_namespace(&workspace_id, namespace)
+      .await
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRequest);
+    assert!(err.invalid_reason.is_some());
+  }
+
+  let namespace = uuid::Uuid::new_v4().to_string();
+  assert!(rules.validate(&namespace).is_ok());
+  c.set_workspace_publish_namespace(&workspace_id, &namespace)
+    .await
+    .unwrap();
+}
+
 #[tokio::test]
 async fn test_publish_doc() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -87,6 +168,8 @@ async fn test_publish_doc() {
           metadata: MyCustomMetadata {
             title: "my_title_1".to_string(),
           },
+          content_hash: None,
+          metadata_version: None,
         },
         data: "yrs_encoded_data_1".as_bytes(),
       },
@@ -97,6 +180,8 @@ async fn test_publish_doc() {
           metadata: MyCustomMetadata {
             title: "my_title_2".to_string(),
           },
+          content_hash: None,
+          metadata_version: None,
         },
         data: "yrs_encoded_data_2".as_bytes(),
       },
@@ -181,6 +266,8 @@ async fn test_publish_load_test() {
         metadata: MyCustomMetadata {
           title: format!("title_{}", i),
         },
+        content_hash: None,
+        metadata_version: None,
       },
       data: vec![0; 100_000], // 100 KB
     })
@@ -189,18 +276,1784 @@ async fn test_publish_load_test() {
   c.publish_collabs(&workspace_id, collabs).await.unwrap();
 }
 
-async fn get_first_workspace_string(c: &client_api::Client) -> String {
-  c.get_workspaces()
+#[tokio::test]
+async fn test_publish_collabs_stream() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  // 250 collabs, published by the server in several batches
+  let alive = Arc::new(AtomicUsize::new(0));
+  let peak_alive = Arc::new(AtomicUsize::new(0));
+  let items = {
+    let alive = alive.clone();
+    let peak_alive = peak_alive.clone();
+    futures::stream::iter(0..250).map(move |i| PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: uuid::Uuid::new_v4(),
+        publish_name: format!("stream-publish-name-{}", i),
+        metadata: MyCustomMetadata {
+          title: format!("title_{}", i),
+        },
+        content_hash: None,
+        metadata_version: None,
+      },
+      data: CountedData::new(vec![0; 100_000], alive.clone(), &peak_alive), // 100 KB
+    })
+  };
+  let published_view_ids = c
+    .publish_collabs_stream(&workspace_id, items)
+    .await
+    .unwrap();
+  assert_eq!(published_view_ids.len(), 250);
+
+  // the items are sent as they are produced instead of being collected first: fewer items than
+  // a single server batch are alive at once
+  assert!(peak_alive.load(Ordering::SeqCst) < PUBLISH_STREAM_BATCH_MAX_ITEMS);
+  assert_eq!(alive.load(Ordering::SeqCst), 0);
+
+  let blob = localhost_client()
+    .get_published_collab_blob(&my_namespace, "stream-publish-name-249")
+    .await
+    .unwrap();
+  assert_eq!(blob.len(), 100_000);
+}
+
+#[tokio::test]
+async fn test_publish_cancelled_batch_publishes_nothing() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id.to_string(), &my_namespace)
+    .await
+    .unwrap();
+
+  let view_ids: Vec<uuid::Uuid> = (0..10).map(|_| uuid::Uuid::new_v4()).collect();
+  let collabs: Vec<PublishCollabItem<MyCustomMetadata, Vec<u8>>> = view_ids
+    .iter()
+    .enumerate()
+    .map(|(i, view_id)| PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: *view_id,
+        publish_name: format!("cancelled-publish-name-{}", i),
+        metadata: MyCustomMetadata {
+          title: format!("title_{}", i),
+        },
+        content_hash: None,
+        metadata_version: None,
+      },
+      data: vec![0; 100_000], // 100 KB
+    })
+    .collect();
+
+  // the body fails after the first half of the batch, so the server never reads the end of the
+  // batch
+  let body = PublishCollabItemStream::new(collabs)
+    .take(view_ids.len() / 2)
+    .chain(futures::stream::once(async {
+      Err(std::io::Error::new(
+        std::io::ErrorKind::Interrupted,
+        "publish cancelled",
+      ))
+    }));
+  let url = format!("{}/api/workspace/{}/publish", c.base_url, workspace_id);
+  let result = c
+    .http_client_with_auth(Method::POST, &url)
     .await
     .unwrap()
-    .0
-    .first()
+    .body(reqwest::Body::wrap_stream(body))
+    .send()
+    .await;
+  if let Ok(resp) = result {
+    assert!(!resp.status().is_success());
+  }
+
+  let guest_client = localhost_client();
+  for view_id in &view_ids {
+    assert!(guest_client
+      .get_published_collab_info(view_id)
+      .await
+      .is_err());
+  }
+}
+
+#[tokio::test]
+async fn test_publish_localized_title() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id.to_string(), &my_namespace)
+    .await
+    .unwrap();
+
+  let publish_name = "localized-publish-name";
+  let view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: serde_json::json!({
+          "title": "my_title",
+          "localized_titles": {
+            "en": "my_title_en",
+            "fr": "mon_titre_fr",
+          },
+        }),
+        content_hash: None,
+        metadata_version: None,
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  {
+    // no Accept-Language, the default title is returned
+    let published_collab = guest_client
+      .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+      .await
+      .unwrap();
+    assert_eq!(published_collab.title, "my_title");
+    let publish_info = guest_client
+      .get_published_collab_info(&view_id)
+      .await
+      .unwrap();
+    assert_eq!(publish_info.title, Some("my_title".to_string()));
+  }
+  {
+    let published_collab = guest_client
+      .get_published_collab_localized::<MyCustomMetadata>(&my_namespace, publish_name, "fr")
+      .await
+      .unwrap();
+    assert_eq!(published_collab.title, "mon_titre_fr");
+    let publish_info = guest_client
+      .get_published_collab_info_localized(&view_id, "fr")
+      .await
+      .unwrap();
+    assert_eq!(publish_info.title, Some("mon_titre_fr".to_string()));
+  }
+  {
+    // region subtag falls back to the primary language
+    let published_collab = guest_client
+      .get_published_collab_localized::<MyCustomMetadata>(
+        &my_namespace,
+        publish_name,
+        "de;q=0.5,en-US,en;q=0.9",
+      )
+      .await
+      .unwrap();
+    assert_eq!(published_collab.title, "my_title_en");
+    let publish_info = guest_client
+      .get_published_collab_info_localized(&view_id, "en-GB")
+      .await
+      .unwrap();
+    assert_eq!(publish_info.title, Some("my_title_en".to_string()));
+  }
+  {
+    // unknown language falls back to the default title
+    let published_collab = guest_client
+      .get_published_collab_localized::<MyCustomMetadata>(&my_namespace, publish_name, "de")
+      .await
+      .unwrap();
+    assert_eq!(published_collab.title, "my_title");
+  }
+  {
+    // a language with q=0 is not acceptable
+    let published_collab = guest_client
+      .get_published_collab_localized::<MyCustomMetadata>(&my_namespace, publish_name, "fr;q=0,de")
+      .await
+      .unwrap();
+    assert_eq!(published_collab.title, "my_title");
+    let publish_info = guest_client
+      .get_published_collab_info_localized(&view_id, "fr;q=0.0,en;q=0.5")
+      .await
+      .unwrap();
+    assert_eq!(publish_info.title, Some("my_title_en".to_string()));
+  }
+}
+
+#[tokio::test]
+async fn test_publish_metadata_with_raw() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id.to_string(), &my_namespace)
+    .await
+    .unwrap();
+
+  let publish_name = "raw-metadata-publish-name";
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: uuid::Uuid::new_v4(),
+        publish_name: publish_name.to_string(),
+        metadata: serde_json::json!({
+          "title": "my_title",
+          "icon": "🚀",
+        }),
+        content_hash: None,
+        metadata_version: None,
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  let (published_collab, raw) = guest_client
+    .get_published_collab_with_raw::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(published_collab.title, "my_title");
+  assert_eq!(raw["title"], "my_title");
+  assert_eq!(raw["icon"], "🚀");
+}
+
+#[tokio::test]
+async fn test_publish_og_metadata() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id.to_string(), &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let doc_state = {
+    let collab = Arc::new(MutexCollab::new(Collab::new_with_origin(
+      CollabOrigin::Empty,
+      &view_id.to_string(),
+      vec![],
+      false,
+    )));
+    let document =
+      Document::create_with_data(collab, get_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap().doc_state.to_vec()
+  };
+
+  let publish_name = "og-publish-name";
+  let publish_doc = |title: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: serde_json::json!({
+        "view": {
+          "name": title,
+          "layout": 0,
+          "extra": r#"{"cover":{"type":"custom","value":"https://appflowy.io/cover.png"}}"#,
+        },
+      }),
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: doc_state.clone(),
+  };
+  c.publish_collabs::<serde_json::Value, Vec<u8>>(&workspace_id, vec![publish_doc("my_doc")])
+    .await
+    .unwrap();
+
+  let guest_client = localhost_client();
+  let og_metadata = guest_client
+    .get_published_og_metadata(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(og_metadata.title, "my_doc");
+  assert!(og_metadata
+    .description
     .unwrap()
-    .workspace_id
-    .to_string()
+    .starts_with("Welcome to AppFlowy!"));
+  assert_eq!(
+    og_metadata.image,
+    Some("https://appflowy.io/cover.png".to_string())
+  );
+
+  // republish regenerates the metadata
+  c.publish_collabs::<serde_json::Value, Vec<u8>>(&workspace_id, vec![publish_doc("my_doc_2")])
+    .await
+    .unwrap();
+  let og_metadata = guest_client
+    .get_published_og_metadata(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(og_metadata.title, "my_doc_2");
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct MyCustomMetadata {
-  title: String,
+#[tokio::test]
+async fn test_repoint_published_view() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id.to_string(), &my_namespace)
+    .await
+    .unwrap();
+
+  let old_view_id = uuid::Uuid::new_v4();
+  let new_view_id = uuid::Uuid::new_v4();
+  for view_id in [old_view_id, new_view_id] {
+    let object_id = view_id.to_string();
+    c.create_collab(CreateCollabParams {
+      encoded_collab_v1: test_encode_collab_v1(&object_id, "title", "hello world")
+        .encode_to_bytes()
+        .unwrap(),
+      object_id,
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+  }
+
+  let publish_name = "repoint-publish-name";
+  let publish_item = |view_id: uuid::Uuid, title: &str, data: &'static str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: title.to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: data.as_bytes(),
+  };
+  c.publish_collabs(
+    &workspace_id,
+    vec![publish_item(old_view_id, "old_title", "old_data")],
+  )
+  .await
+  .unwrap();
+
+  // a view that does not exist can't be used
+  let err = c
+    .repoint_published_view(&workspace_id, publish_name, uuid::Uuid::new_v4())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  c.repoint_published_view(&workspace_id, publish_name, new_view_id)
+    .await
+    .unwrap();
+
+  let guest_client = localhost_client();
+  let publish_info = guest_client
+    .get_published_collab_info(&new_view_id)
+    .await
+    .unwrap();
+  assert_eq!(publish_info.namespace, Some(my_namespace.clone()));
+  assert_eq!(publish_info.publish_name, publish_name);
+  assert!(guest_client
+    .get_published_collab_info(&old_view_id)
+    .await
+    .is_err());
+
+  // publishing the new view updates the content served by the same url
+  c.publish_collabs(
+    &workspace_id,
+    vec![publish_item(new_view_id, "new_title", "new_data")],
+  )
+  .await
+  .unwrap();
+  let published_collab = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(published_collab.title, "new_title");
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "new_data");
+}
+
+#[tokio::test]
+async fn test_publish_audit_log() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id.to_string(), &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  for title in ["my_title", "my_new_title"] {
+    c.publish_collabs::<MyCustomMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "audit-log-publish-name".to_string(),
+          metadata: MyCustomMetadata {
+            title: title.to_string(),
+          },
+          content_hash: None,
+          metadata_version: None,
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+  }
+  c.unpublish_collabs(&workspace_id, &[view_id])
+    .await
+    .unwrap();
+
+  let audit_log = c
+    .get_publish_audit_log(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  let actions = audit_log
+    .iter()
+    .map(|entry| entry.action.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(
+    actions,
+    vec![
+      PublishAuditAction::Publish,
+      PublishAuditAction::Publish,
+      PublishAuditAction::Unpublish
+    ]
+  );
+  assert!(audit_log.iter().all(|entry| entry.view_id == view_id));
+
+  // only the owner of the workspace can read the audit log
+  let (other, _) = generate_unique_registered_user_client().await;
+  assert!(other
+    .get_publish_audit_log(&workspace_id, &view_id)
+    .await
+    .is_err());
+}
+
+/// Requires the server to run with `APPFLOWY_PUBLISH_BLOB_STORAGE=s3`, run it with
+/// `cargo test test_publish_large_blob_in_object_storage -- --ignored`.
+#[ignore]
+#[tokio::test]
+async fn test_publish_large_blob_in_object_storage() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id.to_string(), &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "large-blob-publish-name";
+  let publish_item = |data: Vec<u8>| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data,
+  };
+
+  // blobs larger than the default threshold (1 MB) are stored in the S3 bucket
+  let large_blob: Vec<u8> = (0..2_000_000).map(|i| (i % 251) as u8).collect();
+  c.publish_collabs(&workspace_id, vec![publish_item(large_blob.clone())])
+    .await
+    .unwrap();
+  let guest_client = localhost_client();
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, large_blob);
+
+  // republishing with a small blob moves it back to Postgres
+  c.publish_collabs(&workspace_id, vec![publish_item(b"small_data".to_vec())])
+    .await
+    .unwrap();
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "small_data");
+
+  // the versions reference the blobs in the bucket, which are kept until the versions are deleted
+  c.restore_publish_version(&workspace_id, &view_id, 1, 2)
+    .await
+    .unwrap();
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, large_blob);
+
+  c.unpublish_collabs(&workspace_id, &[view_id])
+    .await
+    .unwrap();
+  assert!(guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_list_workspace_published_databases() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace = c.get_workspaces().await.unwrap().0.pop().unwrap();
+  let workspace_id = workspace.workspace_id.to_string();
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  // a database with two linked views, and a database without published views
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let grid_view_id = uuid::Uuid::new_v4();
+  let board_view_id = uuid::Uuid::new_v4();
+  let database_storage_id = workspace.database_storage_id.to_string();
+  let collab = Collab::new_with_origin(CollabOrigin::Empty, &database_storage_id, vec![], false);
+  collab.with_origin_transact_mut(|txn| {
+    let databases = [
+      (
+        database_id.clone(),
+        vec![grid_view_id.to_string(), board_view_id.to_string()],
+      ),
+      (
+        uuid::Uuid::new_v4().to_string(),
+        vec![uuid::Uuid::new_v4().to_string()],
+      ),
+    ]
+    .into_iter()
+    .map(|(database_id, views)| {
+      MapPrelim::from(HashMap::from([
+        ("database_id".to_string(), Any::from(database_id)),
+        ("views".to_string(), Any::from(views)),
+      ]))
+    })
+    .collect::<Vec<_>>();
+    collab.create_array_with_txn(txn, WORKSPACE_DATABASES, databases);
+  });
+  c.create_collab(CreateCollabParams {
+    encoded_collab_v1: collab
+      .encode_collab_v1(|_| Ok::<(), anyhow::Error>(()))
+      .unwrap()
+      .encode_to_bytes()
+      .unwrap(),
+    object_id: database_storage_id,
+    collab_type: CollabType::WorkspaceDatabase,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+
+  let publish_item = |view_id: uuid::Uuid, publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: publish_name.to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+  c.publish_collabs(
+    &workspace_id,
+    vec![
+      publish_item(grid_view_id, "grid-publish-name"),
+      publish_item(board_view_id, "board-publish-name"),
+      publish_item(uuid::Uuid::new_v4(), "document-publish-name"),
+    ],
+  )
+  .await
+  .unwrap();
+
+  let published_databases = c
+    .list_workspace_published_databases(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(published_databases.len(), 1);
+  assert_eq!(published_databases[0].database_id, database_id);
+  let mut view_ids = published_databases[0]
+    .published_views
+    .iter()
+    .map(|info| info.view_id)
+    .collect::<Vec<_>>();
+  view_ids.sort();
+  let mut expected_view_ids = vec![grid_view_id, board_view_id];
+  expected_view_ids.sort();
+  assert_eq!(view_ids, expected_view_ids);
+  assert!(published_databases[0]
+    .published_views
+    .iter()
+    .all(|info| info.namespace == Some(my_namespace.clone())));
+}
+
+#[tokio::test]
+async fn test_publish_name_already_taken() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let publish_name = "taken-publish-name";
+  let publish_item = |view_id: uuid::Uuid, publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+  let view_id_1 = uuid::Uuid::new_v4();
+  c.publish_collabs(&workspace_id, vec![publish_item(view_id_1, publish_name)])
+    .await
+    .unwrap();
+
+  // republishing the same view under its own name is allowed
+  c.publish_collabs(&workspace_id, vec![publish_item(view_id_1, publish_name)])
+    .await
+    .unwrap();
+
+  let view_id_2 = uuid::Uuid::new_v4();
+  let err = c
+    .publish_collabs(&workspace_id, vec![publish_item(view_id_2, publish_name)])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishNameAlreadyTaken);
+  assert!(err.message.contains(&view_id_1.to_string()));
+
+  // two views of the same batch can't use the same name either
+  let err = c
+    .publish_collabs(
+      &workspace_id,
+      vec![
+        publish_item(uuid::Uuid::new_v4(), "batch-publish-name"),
+        publish_item(uuid::Uuid::new_v4(), "batch-publish-name"),
+      ],
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishNameAlreadyTaken);
+
+  let guest_client = localhost_client();
+  let publish_info = guest_client
+    .get_published_collab_info(&view_id_1)
+    .await
+    .unwrap();
+  assert_eq!(publish_info.publish_name, publish_name);
+  assert!(guest_client
+    .get_published_collab_info(&view_id_2)
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_unpublish_all_collabs() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  // nothing is published yet
+  let unpublished_view_ids = c.unpublish_all_collabs(&workspace_id).await.unwrap();
+  assert!(unpublished_view_ids.is_empty());
+
+  let view_ids: Vec<uuid::Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+  let collabs: Vec<PublishCollabItem<MyCustomMetadata, &[u8]>> = view_ids
+    .iter()
+    .enumerate()
+    .map(|(i, view_id)| PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: *view_id,
+        publish_name: format!("unpublish-all-name-{}", i),
+        metadata: MyCustomMetadata {
+          title: format!("title_{}", i),
+        },
+        content_hash: None,
+        metadata_version: None,
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    })
+    .collect();
+  c.publish_collabs(&workspace_id, collabs).await.unwrap();
+
+  // only the owner of the workspace can unpublish all the views
+  let (other, _) = generate_unique_registered_user_client().await;
+  assert!(other.unpublish_all_collabs(&workspace_id).await.is_err());
+
+  // disabling publishing does not prevent unpublishing
+  c.set_workspace_publishing_enabled(&workspace_id, false)
+    .await
+    .unwrap();
+  let mut unpublished_view_ids = c.unpublish_all_collabs(&workspace_id).await.unwrap();
+  unpublished_view_ids.sort();
+  let mut expected_view_ids = view_ids.clone();
+  expected_view_ids.sort();
+  assert_eq!(unpublished_view_ids, expected_view_ids);
+
+  let guest_client = localhost_client();
+  for view_id in &view_ids {
+    assert!(guest_client
+      .get_published_collab_info(view_id)
+      .await
+      .is_err());
+  }
+  let unpublished_view_ids = c.unpublish_all_collabs(&workspace_id).await.unwrap();
+  assert!(unpublished_view_ids.is_empty());
+}
+
+#[tokio::test]
+async fn test_set_publish_name() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let publish_item = |view_id: uuid::Uuid, publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+  let view_id = uuid::Uuid::new_v4();
+  let other_view_id = uuid::Uuid::new_v4();
+  c.publish_collabs(
+    &workspace_id,
+    vec![
+      publish_item(view_id, "old-publish-name"),
+      publish_item(other_view_id, "other-publish-name"),
+    ],
+  )
+  .await
+  .unwrap();
+
+  c.set_publish_name(&workspace_id, &view_id, "new-publish-name")
+    .await
+    .unwrap();
+  let guest_client = localhost_client();
+  let publish_info = guest_client
+    .get_published_collab_info(&view_id)
+    .await
+    .unwrap();
+  assert_eq!(publish_info.publish_name, "new-publish-name");
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, "new-publish-name")
+    .await
+    .unwrap();
+  assert_eq!(blob, "yrs_encoded_data");
+  assert!(guest_client
+    .get_published_collab_blob(&my_namespace, "old-publish-name")
+    .await
+    .is_err());
+
+  // the name of another published view can't be used
+  let err = c
+    .set_publish_name(&workspace_id, &view_id, "other-publish-name")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishNameAlreadyTaken);
+  assert!(err.message.contains(&other_view_id.to_string()));
+
+  // the name must follow the same rules as when publishing
+  let err = c
+    .set_publish_name(&workspace_id, &view_id, "invalid publish name")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let audit_log = c
+    .get_publish_audit_log(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(audit_log.last().unwrap().action, PublishAuditAction::Rename);
+}
+
+#[tokio::test]
+async fn test_published_view_access() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  c.publish_collabs(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: "private-publish-name".to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+        content_hash: None,
+        metadata_version: None,
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let settings = c
+    .update_workspace_settings(
+      &workspace_id,
+      &AFWorkspaceSettingsChange::new().published_view_access(PublishedViewAccess::WorkspaceMember),
+    )
+    .await
+    .unwrap();
+  assert_eq!(
+    settings.published_view_access,
+    PublishedViewAccess::WorkspaceMember
+  );
+
+  // guests must log in
+  let guest_client = localhost_client();
+  let err = guest_client
+    .get_published_collab_blob(&my_namespace, "private-publish-name")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotLoggedIn);
+  let err = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, "private-publish-name")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotLoggedIn);
+  let err = guest_client
+    .get_published_og_metadata(&my_namespace, "private-publish-name")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotLoggedIn);
+  let err = guest_client
+    .get_published_collab_info(&view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotLoggedIn);
+
+  // users that are not members of the workspace can't read the published view
+  let (other_client, _other_user) = generate_unique_registered_user_client().await;
+  let err = other_client
+    .get_published_collab_blob(&my_namespace, "private-publish-name")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = other_client
+    .get_published_collab_info(&view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // members of the workspace can
+  let blob = c
+    .get_published_collab_blob(&my_namespace, "private-publish-name")
+    .await
+    .unwrap();
+  assert_eq!(blob, "yrs_encoded_data");
+  let info = c.get_published_collab_info(&view_id).await.unwrap();
+  assert_eq!(info.publish_name, "private-publish-name");
+  c.get_published_og_metadata(&my_namespace, "private-publish-name")
+    .await
+    .unwrap();
+
+  // any logged in user can read the published view when only login is required
+  c.update_workspace_settings(
+    &workspace_id,
+    &AFWorkspaceSettingsChange::new().published_view_access(PublishedViewAccess::LoggedIn),
+  )
+  .await
+  .unwrap();
+  let blob = other_client
+    .get_published_collab_blob(&my_namespace, "private-publish-name")
+    .await
+    .unwrap();
+  assert_eq!(blob, "yrs_encoded_data");
+  let err = guest_client
+    .get_published_collab_blob(&my_namespace, "private-publish-name")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotLoggedIn);
+}
+
+#[tokio::test]
+async fn test_unpublish_collabs_partial_failure() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let publish_item = |view_id: uuid::Uuid, publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+  let view_id_1 = uuid::Uuid::new_v4();
+  let view_id_2 = uuid::Uuid::new_v4();
+  c.publish_collabs(
+    &workspace_id,
+    vec![
+      publish_item(view_id_1, "publish-name-1"),
+      publish_item(view_id_2, "publish-name-2"),
+    ],
+  )
+  .await
+  .unwrap();
+
+  let never_published_view_id = uuid::Uuid::new_v4();
+  let results = c
+    .unpublish_collabs(
+      &workspace_id,
+      &[view_id_1, never_published_view_id, view_id_2],
+    )
+    .await
+    .unwrap();
+  let results = results
+    .into_iter()
+    .map(|result| (result.view_id, result.result))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    results,
+    vec![
+      (view_id_1, UnpublishResult::Unpublished),
+      (never_published_view_id, UnpublishResult::NotFound),
+      (view_id_2, UnpublishResult::Unpublished),
+    ]
+  );
+
+  let guest_client = localhost_client();
+  for publish_name in ["publish-name-1", "publish-name-2"] {
+    let err = guest_client
+      .get_published_collab_blob(&my_namespace, publish_name)
+      .await
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::RecordNotFound);
+  }
+
+  // unpublishing again reports the views as not found
+  let results = c
+    .unpublish_collabs(&workspace_id, &[view_id_1])
+    .await
+    .unwrap();
+  assert_eq!(results[0].result, UnpublishResult::NotFound);
+}
+
+#[tokio::test]
+async fn test_restore_publish_version() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_item = |title: &str, data: &'static str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: "versioned-publish-name".to_string(),
+      metadata: MyCustomMetadata {
+        title: title.to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: data.as_bytes(),
+  };
+  c.publish_collabs(&workspace_id, vec![publish_item("title_1", "data_1")])
+    .await
+    .unwrap();
+  c.publish_collabs(&workspace_id, vec![publish_item("title_2", "data_2")])
+    .await
+    .unwrap();
+
+  let versions = c
+    .list_publish_versions(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    versions.iter().map(|v| v.version).collect::<Vec<_>>(),
+    vec![2, 1]
+  );
+
+  let guest_client = localhost_client();
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, "versioned-publish-name")
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_2");
+
+  c.restore_publish_version(&workspace_id, &view_id, 1, 2)
+    .await
+    .unwrap();
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, "versioned-publish-name")
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_1");
+  let published_collab = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, "versioned-publish-name")
+    .await
+    .unwrap();
+  assert_eq!(published_collab.title, "title_1");
+
+  // the restore is recorded as the newest version
+  let versions = c
+    .list_publish_versions(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 3);
+  assert_eq!(versions[0].version, 3);
+
+  // the restored content keeps its hash, so publishing it again is skipped as unchanged
+  let mut item = publish_item("title_1", "data_1");
+  item.meta.content_hash = Some(format!("{:x}", Sha256::digest("data_1")));
+  let published_view_ids = c.publish_collabs(&workspace_id, vec![item]).await.unwrap();
+  assert!(published_view_ids.is_empty());
+
+  // the restore fails when the view was published again since the versions were listed
+  let err = c
+    .restore_publish_version(&workspace_id, &view_id, 2, 2)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishVersionConflict);
+
+  let err = c
+    .restore_publish_version(&workspace_id, &view_id, 42, 3)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn test_republish_skips_unchanged_collabs() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id_1 = uuid::Uuid::new_v4();
+  let view_id_2 = uuid::Uuid::new_v4();
+  let publish_item =
+    |view_id: uuid::Uuid, publish_name: &str, data: &'static str| PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+        content_hash: Some(format!("{:x}", Sha256::digest(data))),
+        metadata_version: None,
+      },
+      data: data.as_bytes(),
+    };
+  let published_view_ids = c
+    .publish_collabs(
+      &workspace_id,
+      vec![
+        publish_item(view_id_1, "incremental-1", "data_1"),
+        publish_item(view_id_2, "incremental-2", "data_2"),
+      ],
+    )
+    .await
+    .unwrap();
+  assert_eq!(published_view_ids, vec![view_id_1, view_id_2]);
+
+  // only the view whose content changed is published again
+  let published_view_ids = c
+    .publish_collabs(
+      &workspace_id,
+      vec![
+        publish_item(view_id_1, "incremental-1", "data_1"),
+        publish_item(view_id_2, "incremental-2", "data_2_changed"),
+      ],
+    )
+    .await
+    .unwrap();
+  assert_eq!(published_view_ids, vec![view_id_2]);
+
+  let versions = c
+    .list_publish_versions(&workspace_id, &view_id_1)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 1);
+  let versions = c
+    .list_publish_versions(&workspace_id, &view_id_2)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 2);
+
+  let guest_client = localhost_client();
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, "incremental-2")
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_2_changed");
+
+  // the content hash must be the one of the data
+  let mut item = publish_item(view_id_1, "incremental-1", "data_1_changed");
+  item.meta.content_hash = Some(format!("{:x}", Sha256::digest("data_1")));
+  let err = c
+    .publish_collabs(&workspace_id, vec![item])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let mut item = publish_item(view_id_1, "incremental-1", "data_1_changed");
+  item.meta.content_hash = Some("not-a-sha256".to_string());
+  let err = c
+    .publish_collabs(&workspace_id, vec![item])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn test_get_namespace_published_views() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id_1 = uuid::Uuid::new_v4();
+  let view_id_2 = uuid::Uuid::new_v4();
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: view_id_1,
+          publish_name: "index-publish-name-1".to_string(),
+          metadata: serde_json::json!({
+            "title": "my_title_1",
+            "icon": "🚀",
+          }),
+          content_hash: None,
+          metadata_version: None,
+        },
+        data: "yrs_encoded_data_1".as_bytes(),
+      },
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: view_id_2,
+          publish_name: "index-publish-name-2".to_string(),
+          metadata: serde_json::json!({
+            "title": "my_title_2",
+          }),
+          content_hash: None,
+          metadata_version: None,
+        },
+        data: "yrs_encoded_data_2".as_bytes(),
+      },
+    ],
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  let views = guest_client
+    .get_namespace_published_views(&my_namespace)
+    .await
+    .unwrap();
+  let views = views
+    .into_iter()
+    .map(|view| (view.view_id, view))
+    .collect::<HashMap<_, _>>();
+  assert_eq!(views.len(), 2);
+
+  let view_1 = &views[&view_id_1];
+  assert_eq!(view_1.publish_name, "index-publish-name-1");
+  assert_eq!(view_1.title.as_deref(), Some("my_title_1"));
+  assert_eq!(view_1.icon.as_deref(), Some("🚀"));
+
+  let view_2 = &views[&view_id_2];
+  assert_eq!(view_2.publish_name, "index-publish-name-2");
+  assert_eq!(view_2.title.as_deref(), Some("my_title_2"));
+  assert_eq!(view_2.icon, None);
+}
+
+#[tokio::test]
+async fn test_published_view_stats() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "stats-doc";
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+        content_hash: None,
+        metadata_version: None,
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  let (_, etag) = guest_client
+    .get_published_collab_blob_with_etag(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  // the revalidation of an unchanged blob is not counted as a read
+  assert!(guest_client
+    .get_published_collab_blob_if_changed(&my_namespace, publish_name, &etag.unwrap())
+    .await
+    .unwrap()
+    .is_none());
+  c.get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+
+  // visits are recorded in the background
+  let mut stats = c
+    .get_published_view_stats(&workspace_id, &view_id, false)
+    .await
+    .unwrap();
+  for _ in 0..20 {
+    if stats.view_count == 3 {
+      break;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    stats = c
+      .get_published_view_stats(&workspace_id, &view_id, false)
+      .await
+      .unwrap();
+  }
+  assert_eq!(stats.view_count, 3);
+  assert_eq!(stats.unique_visitor_count, 2);
+
+  let stats = c
+    .get_published_view_stats(&workspace_id, &view_id, true)
+    .await
+    .unwrap();
+  assert_eq!(stats.view_count, 2);
+  assert_eq!(stats.unique_visitor_count, 1);
+
+  // only the owner can read the stats
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  assert!(other_client
+    .get_published_view_stats(&workspace_id, &view_id, false)
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_publish_retry_with_idempotency_key() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "idempotent-doc";
+  let publish_item = |data: &'static str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: data.as_bytes(),
+  };
+  let idempotency_key = uuid::Uuid::new_v4().to_string();
+  let published_view_ids = c
+    .publish_collabs_with_idempotency_key(
+      &workspace_id,
+      vec![publish_item("data_1")],
+      &idempotency_key,
+    )
+    .await
+    .unwrap();
+  assert_eq!(published_view_ids, vec![view_id]);
+
+  // the retried request returns the original result without publishing again
+  let published_view_ids = c
+    .publish_collabs_with_idempotency_key(
+      &workspace_id,
+      vec![publish_item("data_2")],
+      &idempotency_key,
+    )
+    .await
+    .unwrap();
+  assert_eq!(published_view_ids, vec![view_id]);
+  let versions = c
+    .list_publish_versions(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 1);
+  let blob = localhost_client()
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_1");
+
+  // a new key publishes again
+  let hashed_item = || {
+    let mut item = publish_item("data_2");
+    item.meta.content_hash = Some(format!("{:x}", Sha256::digest("data_2")));
+    item
+  };
+  c.publish_collabs_with_idempotency_key(
+    &workspace_id,
+    vec![hashed_item()],
+    &uuid::Uuid::new_v4().to_string(),
+  )
+  .await
+  .unwrap();
+  let blob = localhost_client()
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_2");
+
+  // the key is recorded even when nothing is published because the content is unchanged
+  let idempotency_key = uuid::Uuid::new_v4().to_string();
+  let published_view_ids = c
+    .publish_collabs_with_idempotency_key(&workspace_id, vec![hashed_item()], &idempotency_key)
+    .await
+    .unwrap();
+  assert!(published_view_ids.is_empty());
+  let published_view_ids = c
+    .publish_collabs_with_idempotency_key(
+      &workspace_id,
+      vec![publish_item("data_3")],
+      &idempotency_key,
+    )
+    .await
+    .unwrap();
+  assert!(published_view_ids.is_empty());
+  let blob = localhost_client()
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_2");
+
+  // concurrent requests with the same key publish once and return the same result
+  let idempotency_key = uuid::Uuid::new_v4().to_string();
+  let (res_1, res_2) = tokio::join!(
+    c.publish_collabs_with_idempotency_key(
+      &workspace_id,
+      vec![publish_item("data_4")],
+      &idempotency_key,
+    ),
+    c.publish_collabs_with_idempotency_key(
+      &workspace_id,
+      vec![publish_item("data_4")],
+      &idempotency_key,
+    ),
+  );
+  assert_eq!(res_1.unwrap(), vec![view_id]);
+  assert_eq!(res_2.unwrap(), vec![view_id]);
+  let versions = c
+    .list_publish_versions(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 3);
+}
+
+#[tokio::test]
+async fn test_workspace_publishing_disabled() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let settings = c.get_workspace_settings(&workspace_id).await.unwrap();
+  assert!(settings.publishing_enabled);
+
+  let publish_item = || PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id: uuid::Uuid::new_v4(),
+      publish_name: uuid::Uuid::new_v4().to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+
+  c.set_workspace_publishing_enabled(&workspace_id, false)
+    .await
+    .unwrap();
+  let err = c
+    .publish_collabs(&workspace_id, vec![publish_item()])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishDisabled);
+  let err = c
+    .set_workspace_publish_namespace(&workspace_id, &uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishDisabled);
+
+  c.set_workspace_publishing_enabled(&workspace_id, true)
+    .await
+    .unwrap();
+  c.publish_collabs(&workspace_id, vec![publish_item()])
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_workspace_min_publish_role() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_item = || PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: "min-publish-role".to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+
+  // a member can publish under the default policy
+  member
+    .api_client
+    .publish_collabs(&workspace_id, vec![publish_item()])
+    .await
+    .unwrap();
+
+  owner
+    .api_client
+    .set_workspace_min_publish_role(&workspace_id, AFRole::Owner)
+    .await
+    .unwrap();
+  let err = member
+    .api_client
+    .publish_collabs(&workspace_id, vec![publish_item()])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  // the policy only restricts publishing, the publisher can still unpublish their view
+  let results = member
+    .api_client
+    .unpublish_collabs(&workspace_id, &[view_id])
+    .await
+    .unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].result, UnpublishResult::Unpublished);
+}
+
+#[tokio::test]
+async fn test_get_published_collab_blob_if_changed() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "etag-doc";
+  let publish_item = |data: &'static str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: "my_title".to_string(),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: data.as_bytes(),
+  };
+  c.publish_collabs(&workspace_id, vec![publish_item("data_1")])
+    .await
+    .unwrap();
+
+  let guest_client = localhost_client();
+  let (blob, etag) = guest_client
+    .get_published_collab_blob_with_etag(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_1");
+  let etag = etag.unwrap();
+
+  // unchanged blob is not downloaded again
+  let blob = guest_client
+    .get_published_collab_blob_if_changed(&my_namespace, publish_name, &etag)
+    .await
+    .unwrap();
+  assert!(blob.is_none());
+
+  c.publish_collabs(&workspace_id, vec![publish_item("data_2")])
+    .await
+    .unwrap();
+  let (blob, new_etag) = guest_client
+    .get_published_collab_blob_if_changed(&my_namespace, publish_name, &etag)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(blob, "data_2");
+  assert_ne!(new_etag.unwrap(), etag);
+
+  // the ETag is computed from the data, whether or not the publisher sent a content hash
+  let mut item = publish_item("data_3");
+  item.meta.content_hash = Some(format!("{:x}", Sha256::digest("data_3")));
+  c.publish_collabs(&workspace_id, vec![item]).await.unwrap();
+  let (_, hashed_etag) = guest_client
+    .get_published_collab_blob_with_etag(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  c.publish_collabs(&workspace_id, vec![publish_item("data_1")])
+    .await
+    .unwrap();
+  c.publish_collabs(&workspace_id, vec![publish_item("data_3")])
+    .await
+    .unwrap();
+  let (blob, etag) = guest_client
+    .get_published_collab_blob_with_etag(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "data_3");
+  assert_eq!(etag, hashed_etag);
+}
+
+#[tokio::test]
+async fn test_publish_metadata_version() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, &my_namespace)
+    .await
+    .unwrap();
+
+  let publish_item =
+    |view_id: uuid::Uuid, publish_name: &str, metadata_version: Option<i32>| PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "my_title".to_string(),
+        },
+        content_hash: None,
+        metadata_version,
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    };
+  let unversioned_view_id = uuid::Uuid::new_v4();
+  let versioned_view_id = uuid::Uuid::new_v4();
+  c.publish_collabs(
+    &workspace_id,
+    vec![
+      publish_item(unversioned_view_id, "unversioned", None),
+      publish_item(versioned_view_id, "versioned", Some(2)),
+    ],
+  )
+  .await
+  .unwrap();
+
+  // the metadata published without a version is version 1
+  let guest_client = localhost_client();
+  let publish_info = guest_client
+    .get_published_collab_info(&unversioned_view_id)
+    .await
+    .unwrap();
+  assert_eq!(publish_info.metadata_version, 1);
+  let publish_info = guest_client
+    .get_published_collab_info(&versioned_view_id)
+    .await
+    .unwrap();
+  assert_eq!(publish_info.metadata_version, 2);
+
+  // republishing the view updates the version
+  c.publish_collabs(
+    &workspace_id,
+    vec![publish_item(versioned_view_id, "versioned", Some(3))],
+  )
+  .await
+  .unwrap();
+  let publish_info = guest_client
+    .get_published_collab_info(&versioned_view_id)
+    .await
+    .unwrap();
+  assert_eq!(publish_info.metadata_version, 3);
+}
+
+#[tokio::test]
+async fn test_get_my_published_views() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let first_workspace_id = get_first_workspace_string(&c).await;
+  let second_workspace_id = c
+    .create_workspace(CreateWorkspaceParam {
+      workspace_name: Some("my_second_workspace".to_string()),
+    })
+    .await
+    .unwrap()
+    .workspace_id
+    .to_string();
+
+  let publish_item = |view_id: uuid::Uuid, publish_name: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: format!("title of {}", publish_name),
+      },
+      content_hash: None,
+      metadata_version: None,
+    },
+    data: "yrs_encoded_data".as_bytes(),
+  };
+  let mut expected = vec![];
+  for (workspace_id, publish_name) in [
+    (&first_workspace_id, "first-workspace-view"),
+    (&second_workspace_id, "second-workspace-view"),
+  ] {
+    let namespace = uuid::Uuid::new_v4().to_string();
+    c.set_workspace_publish_namespace(workspace_id, &namespace)
+      .await
+      .unwrap();
+    let view_id = uuid::Uuid::new_v4();
+    c.publish_collabs(workspace_id, vec![publish_item(view_id, publish_name)])
+      .await
+      .unwrap();
+    expected.push((workspace_id.clone(), namespace, view_id, publish_name));
+  }
+
+  // the views published by other users are not listed
+  let (other_client, _other_user) = generate_unique_registered_user_client().await;
+  let other_workspace_id = get_first_workspace_string(&other_client).await;
+  other_client
+    .set_workspace_publish_namespace(&other_workspace_id, &uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  other_client
+    .publish_collabs(
+      &other_workspace_id,
+      vec![publish_item(uuid::Uuid::new_v4(), "other-user-view")],
+    )
+    .await
+    .unwrap();
+
+  let views = c.get_my_published_views().await.unwrap();
+  assert_eq!(views.len(), 2);
+  for (workspace_id, namespace, view_id, publish_name) in expected {
+    let view = views.iter().find(|view| view.view_id == view_id).unwrap();
+    assert_eq!(view.workspace_id.to_string(), workspace_id);
+    assert_eq!(view.namespace.as_deref(), Some(namespace.as_str()));
+    assert_eq!(view.publish_name, publish_name);
+    assert_eq!(view.title, Some(format!("title of {}", publish_name)));
+  }
+  assert!(other_client
+    .get_my_published_views()
+    .await
+    .unwrap()
+    .iter()
+    .all(|view| view.publish_name == "other-user-view"));
+}
+
+async fn get_first_workspace_string(c: &client_api::Client) -> String {
+  c.get_workspaces()
+    .await
+    .unwrap()
+    .0
+    .first()
+    .unwrap()
+    .workspace_id
+    .to_string()
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MyCustomMetadata {
+  title: String,
+}
+
+/// Publish data that counts how many instances are alive, to check that a stream of items is not
+/// buffered.
+struct CountedData {
+  data: Vec<u8>,
+  alive: Arc<AtomicUsize>,
+}
+
+impl CountedData {
+  fn new(data: Vec<u8>, alive: Arc<AtomicUsize>, peak_alive: &AtomicUsize) -> Self {
+    let count = alive.fetch_add(1, Ordering::SeqCst) + 1;
+    peak_alive.fetch_max(count, Ordering::SeqCst);
+    Self { data, alive }
+  }
+}
+
+impl AsRef<[u8]> for CountedData {
+  fn as_ref(&self) -> &[u8] {
+    &self.data
+  }
+}
+
+impl Drop for CountedData {
+  fn drop(&mut self) {
+    self.alive.fetch_sub(1, Ordering::SeqCst);
+  }
 }
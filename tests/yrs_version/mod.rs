@@ -1,3 +1,3 @@
 mod document_test;
 mod folder_test;
-mod util;
+pub(crate) mod util;